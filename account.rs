@@ -0,0 +1,46 @@
+use web3::{
+    types::{Address, H256, U256},
+    Transport,
+};
+
+use crate::{YieldFarmingClient, YieldFarmingError};
+
+/// Shorthands for single-signer clients that send every write from the builder's `default_account`
+impl<T: Transport> YieldFarmingClient<T> {
+    /// The account configured with `YieldFarmingClientBuilder::default_account`
+    pub fn default_account(&self) -> Result<Address, YieldFarmingError> {
+        self.default_account.ok_or(YieldFarmingError::NoDefaultAccount)
+    }
+
+    /// `deposit` from the default account
+    pub async fn deposit_default(&self, amount: U256) -> Result<H256, YieldFarmingError> {
+        self.deposit(amount, self.default_account()?).await
+    }
+
+    /// `withdraw` from the default account
+    pub async fn withdraw_default(&self, amount: U256) -> Result<H256, YieldFarmingError> {
+        self.withdraw(amount, self.default_account()?).await
+    }
+
+    /// `claim_rewards` for the default account
+    pub async fn claim_rewards_default(&self) -> Result<H256, YieldFarmingError> {
+        self.claim_rewards(self.default_account()?).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTransport;
+
+    #[tokio::test]
+    async fn test_default_account_required() {
+        let transport = MockTransport::new();
+        let mut client = YieldFarmingClient::from_transport(transport.clone(), Address::zero(), b"[]").unwrap();
+        assert!(matches!(client.deposit_default(1.into()).await, Err(YieldFarmingError::NoDefaultAccount)));
+        assert!(transport.requests().is_empty());
+
+        client.default_account = Some(Address::repeat_byte(0x01));
+        assert_eq!(client.default_account().unwrap(), Address::repeat_byte(0x01));
+    }
+}
@@ -0,0 +1,80 @@
+use std::str::FromStr;
+use web3::{signing::keccak256, types::Address};
+
+use crate::YieldFarmingError;
+
+/// Parse a hex address, verifying its EIP-55 checksum when it is mixed-case.
+///
+/// All-lowercase and all-uppercase addresses carry no checksum and are accepted
+/// as-is; a mixed-case address whose casing doesn't match its checksum is
+/// rejected with `InvalidChecksum`, as it most likely contains a typo.
+pub fn parse_checked_address(s: &str) -> Result<Address, YieldFarmingError> {
+    let hex = s.strip_prefix("0x").unwrap_or(s);
+    if hex.len() != 40 {
+        return Err(YieldFarmingError::InvalidAddress(s.to_string()));
+    }
+    let address = Address::from_str(hex).map_err(|_| YieldFarmingError::InvalidAddress(s.to_string()))?;
+
+    let has_lower = hex.chars().any(|c| c.is_ascii_lowercase());
+    let has_upper = hex.chars().any(|c| c.is_ascii_uppercase());
+    if has_lower && has_upper && to_checksum(&address)[2..] != *hex {
+        return Err(YieldFarmingError::InvalidChecksum(s.to_string()));
+    }
+
+    Ok(address)
+}
+
+/// Format an address with its EIP-55 mixed-case checksum
+pub fn to_checksum(address: &Address) -> String {
+    let lower = hex::encode(address.as_bytes());
+    let hash = keccak256(lower.as_bytes());
+
+    let mut checksummed = String::with_capacity(42);
+    checksummed.push_str("0x");
+    for (i, c) in lower.chars().enumerate() {
+        let nibble = (hash[i / 2] >> if i % 2 == 0 { 4 } else { 0 }) & 0x0f;
+        checksummed.push(if nibble >= 8 { c.to_ascii_uppercase() } else { c });
+    }
+    checksummed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test vectors from EIP-55
+    const CHECKSUMMED: [&str; 4] = [
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+        "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+        "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+    ];
+
+    #[test]
+    fn test_valid_checksums() {
+        for address in CHECKSUMMED {
+            let parsed = parse_checked_address(address).unwrap();
+            assert_eq!(to_checksum(&parsed), address);
+        }
+    }
+
+    #[test]
+    fn test_unchecksummed_accepted() {
+        let lower = CHECKSUMMED[0].to_lowercase();
+        let upper = format!("0x{}", CHECKSUMMED[0][2..].to_uppercase());
+        assert!(parse_checked_address(&lower).is_ok());
+        assert!(parse_checked_address(&upper).is_ok());
+    }
+
+    #[test]
+    fn test_checksum_mismatch_rejected() {
+        // Flip the case of one letter
+        let typo = CHECKSUMMED[0].replacen("aA", "AA", 1);
+        assert!(matches!(parse_checked_address(&typo), Err(YieldFarmingError::InvalidChecksum(_))));
+        assert!(matches!(parse_checked_address("0x1234"), Err(YieldFarmingError::InvalidAddress(_))));
+        assert!(matches!(
+            parse_checked_address("0xzz00000000000000000000000000000000000000"),
+            Err(YieldFarmingError::InvalidAddress(_))
+        ));
+    }
+}
@@ -0,0 +1,77 @@
+use serde::Serialize;
+use web3::{
+    contract::tokens::{Detokenize, Tokenize},
+    types::Address,
+    Transport,
+};
+
+use crate::{YieldFarmingClient, YieldFarmingError};
+
+/// Who controls the pool and whether it is accepting transactions.
+///
+/// Each field is `None` when the pool's ABI has no matching view.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct AdminInfo {
+    /// `owner()`
+    pub owner: Option<Address>,
+    /// `pendingOwner()` of `Ownable2Step`-style pools, zero when no transfer is in progress
+    pub pending_owner: Option<Address>,
+    /// `paused()`
+    pub paused: Option<bool>,
+}
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Read the pool's `owner()`, `pendingOwner()` and `paused()` views, skipping any its ABI lacks
+    pub async fn contract_admin_info(&self) -> Result<AdminInfo, YieldFarmingError> {
+        let (owner, pending_owner, paused) = futures::try_join!(
+            self.optional_view::<Address, _>("owner", ()),
+            self.optional_view::<Address, _>("pendingOwner", ()),
+            self.optional_view::<bool, _>("paused", ()),
+        )?;
+
+        Ok(AdminInfo { owner, pending_owner, paused })
+    }
+
+    /// Query a view, or `None` if the ABI has no such function
+    pub(crate) async fn optional_view<R, P>(&self, function: &str, params: P) -> Result<Option<R>, YieldFarmingError>
+    where
+        R: Detokenize + Clone + Send + Sync + 'static,
+        P: Tokenize + Clone,
+    {
+        if self.contract.abi().function(function).is_err() {
+            return Ok(None);
+        }
+        self.query_with_retry(function, params).await.map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTransport;
+    use web3::ethabi::Token;
+
+    const OWNABLE_ABI: &[u8] = br#"[
+        {"type":"function","name":"owner","stateMutability":"view","inputs":[],"outputs":[{"name":"","type":"address"}]},
+        {"type":"function","name":"paused","stateMutability":"view","inputs":[],"outputs":[{"name":"","type":"bool"}]}
+    ]"#;
+
+    #[tokio::test]
+    async fn test_admin_info_skips_missing_views() {
+        let transport = MockTransport::new();
+        transport.push_call_result(&[Token::Address(Address::repeat_byte(0x0a))]);
+        transport.push_call_result(&[Token::Bool(true)]);
+
+        let client = YieldFarmingClient::from_transport(transport.clone(), Address::zero(), OWNABLE_ABI).unwrap();
+        let info = client.contract_admin_info().await.unwrap();
+        assert_eq!(
+            info,
+            AdminInfo {
+                owner: Some(Address::repeat_byte(0x0a)),
+                pending_owner: None,
+                paused: Some(true),
+            }
+        );
+        assert_eq!(transport.requests().len(), 2);
+    }
+}
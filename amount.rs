@@ -0,0 +1,115 @@
+use std::fmt;
+use web3::{
+    types::{Address, U256},
+    Transport,
+};
+
+use crate::{units, YieldFarmingClient, YieldFarmingError};
+
+/// A token amount in base units together with the token's decimals
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TokenAmount {
+    pub raw: U256,
+    pub decimals: u8,
+}
+
+impl TokenAmount {
+    pub fn new(raw: U256, decimals: u8) -> Self {
+        Self { raw, decimals }
+    }
+
+    /// Parse a human-readable amount such as `"12.5"`, truncating digits beyond `decimals`
+    pub fn from_decimal_str(value: &str, decimals: u8) -> Option<Self> {
+        units::parse_decimal_str(value, decimals).map(|raw| Self { raw, decimals })
+    }
+
+    /// Approximate human-readable value, for display and price maths only
+    pub fn to_f64(&self) -> f64 {
+        units::from_wei(self.raw, self.decimals)
+    }
+
+    /// Add two amounts, failing with `ArithmeticOverflow` on overflow or `DecimalsMismatch`
+    pub fn checked_add(self, other: Self) -> Result<Self, YieldFarmingError> {
+        self.ensure_same_decimals(other)?;
+        let raw = self.raw.checked_add(other.raw).ok_or(YieldFarmingError::ArithmeticOverflow("token amount sum"))?;
+        Ok(Self { raw, ..self })
+    }
+
+    /// Subtract two amounts, failing with `ArithmeticOverflow` on underflow or `DecimalsMismatch`
+    pub fn checked_sub(self, other: Self) -> Result<Self, YieldFarmingError> {
+        self.ensure_same_decimals(other)?;
+        let raw = self.raw.checked_sub(other.raw).ok_or(YieldFarmingError::ArithmeticOverflow("token amount difference"))?;
+        Ok(Self { raw, ..self })
+    }
+
+    fn ensure_same_decimals(self, other: Self) -> Result<(), YieldFarmingError> {
+        if self.decimals != other.decimals {
+            return Err(YieldFarmingError::DecimalsMismatch { left: self.decimals, right: other.decimals });
+        }
+        Ok(())
+    }
+}
+
+/// Renders the exact amount in whole units, e.g. `1.5` for `1500000` at 6 decimals
+impl fmt::Display for TokenAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&units::format_decimal(self.raw, self.decimals))
+    }
+}
+
+impl From<TokenAmount> for U256 {
+    fn from(amount: TokenAmount) -> Self {
+        amount.raw
+    }
+}
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Get user's staked balance in staking token units
+    pub async fn get_staked_balance_typed(&self, account: Address) -> Result<TokenAmount, YieldFarmingError> {
+        let (raw, decimals) = futures::try_join!(self.get_staked_balance(account), self.token_decimals())?;
+        Ok(TokenAmount::new(raw, decimals))
+    }
+
+    /// Get pending rewards for a user in reward token units
+    pub async fn get_pending_rewards_typed(&self, account: Address) -> Result<TokenAmount, YieldFarmingError> {
+        let (raw, decimals) = futures::try_join!(self.get_pending_rewards(account), self.reward_token_decimals())?;
+        Ok(TokenAmount::new(raw, decimals))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_and_parse() {
+        let amount = TokenAmount::from_decimal_str("1.5", 6).unwrap();
+        assert_eq!(amount.raw, U256::from(1_500_000));
+        assert_eq!(amount.to_string(), "1.5");
+        assert_eq!(TokenAmount::new(42.into(), 18).to_string(), "0.000000000000000042");
+        assert_eq!(TokenAmount::new(7.into(), 0).to_string(), "7");
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = TokenAmount::new(300.into(), 2);
+        let b = TokenAmount::new(100.into(), 2);
+        assert_eq!(a.checked_sub(b).unwrap().to_string(), "2");
+        assert_eq!(a.checked_add(b).unwrap(), TokenAmount::new(400.into(), 2));
+        assert!(matches!(b.checked_sub(a), Err(YieldFarmingError::ArithmeticOverflow(_))));
+        assert!(matches!(
+            a.checked_add(TokenAmount::new(1.into(), 18)),
+            Err(YieldFarmingError::DecimalsMismatch { left: 2, right: 18 })
+        ));
+        assert_eq!(U256::from(a), U256::from(300));
+    }
+
+    #[test]
+    fn test_arithmetic_near_max() {
+        let max = TokenAmount::new(U256::MAX, 18);
+        let one = TokenAmount::new(1.into(), 18);
+        assert!(matches!(max.checked_add(one), Err(YieldFarmingError::ArithmeticOverflow(_))));
+        assert_eq!(max.checked_sub(one).unwrap().checked_add(one).unwrap(), max);
+        assert_eq!(max.checked_sub(max).unwrap().raw, U256::zero());
+    }
+}
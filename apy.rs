@@ -0,0 +1,333 @@
+use futures::{stream, StreamExt, TryStreamExt};
+use std::time::Duration;
+use web3::{
+    contract::Options,
+    types::{Address, BlockNumber, U256},
+    Transport,
+};
+
+use crate::cache::ViewCache;
+use crate::{erc20, fixed, retry, units, PriceOracle, YieldFarmingClient, YieldFarmingError, NATIVE_TOKEN};
+
+/// Seconds in a day
+pub const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Seconds in a 365-day year
+pub const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+
+/// Maximum number of historical APY queries in flight at once
+const APY_HISTORY_CONCURRENCY: usize = 8;
+
+/// Mean and range of APY samples taken by `apy_stats`, as percentages
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ApyStats {
+    pub mean: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+/// `samples` evenly spaced blocks ending at `latest` and spanning `window_blocks`, oldest first
+fn sample_blocks(latest: u64, window_blocks: u64, samples: usize) -> Vec<u64> {
+    let start = latest.saturating_sub(window_blocks);
+    match samples {
+        0 | 1 => vec![latest],
+        _ => (0..samples as u64)
+            .map(|i| start + (latest - start) * i / (samples as u64 - 1))
+            .collect(),
+    }
+}
+
+/// Simple (non-compounding) APY as a percentage from yearly reward value and staked value
+pub fn apy_percentage(yearly_reward_value: f64, staked_value: f64) -> f64 {
+    if staked_value <= 0.0 {
+        return 0.0;
+    }
+    yearly_reward_value / staked_value * 100.0
+}
+
+/// APY as a percentage after subtracting the yearly gas cost of claiming from the yearly reward value
+pub fn net_apy_percentage(yearly_reward_value: f64, yearly_gas_cost: f64, position_value: f64) -> f64 {
+    apy_percentage(yearly_reward_value - yearly_gas_cost, position_value)
+}
+
+/// Rewards per day earned by `staked` out of `total_supply` at `reward_rate` per second
+fn daily_share(reward_rate: U256, staked: U256, total_supply: U256) -> Result<U256, YieldFarmingError> {
+    share_over(reward_rate, staked, total_supply, SECONDS_PER_DAY)
+}
+
+/// Rewards earned over `seconds` by `staked` out of `total_supply` at `reward_rate` per second
+fn share_over(reward_rate: U256, staked: U256, total_supply: U256, seconds: u64) -> Result<U256, YieldFarmingError> {
+    reward_rate
+        .checked_mul(seconds.into())
+        .and_then(|emitted| fixed::checked_mul_div(emitted, staked, total_supply))
+        .ok_or(YieldFarmingError::ArithmeticOverflow("reward share"))
+}
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Compute APY as a percentage from the on-chain reward rate and staked supply.
+    ///
+    /// Works with Synthetix-style contracts exposing `rewardRate()` (reward
+    /// base units per second), `totalSupply()`, `stakingToken()` and
+    /// `rewardToken()`, valued with prices from `oracle`. Returns `0.0` when
+    /// nothing is staked. The math is done in `fixed` point.
+    pub async fn compute_apy(&self, oracle: &impl PriceOracle) -> Result<f64, YieldFarmingError> {
+        let (reward_rate, total_supply, staking_token, reward_token) = futures::try_join!(
+            self.reward_rate(),
+            self.query_with_retry::<U256, _>("totalSupply", ()),
+            self.staking_token(),
+            self.reward_token(),
+        )?;
+        if total_supply.is_zero() {
+            return Ok(0.0);
+        }
+
+        let (staking_decimals, reward_decimals, stake_price, reward_price) = futures::try_join!(
+            self.token_decimals(),
+            self.reward_token_decimals(),
+            oracle.price_usd(staking_token),
+            oracle.price_usd(reward_token),
+        )?;
+
+        let rewards_per_year = fixed::from_base_units(reward_rate, reward_decimals).saturating_mul(SECONDS_PER_YEAR.into());
+        let staked = fixed::from_base_units(total_supply, staking_decimals);
+        let yearly_reward_value = fixed::mul(rewards_per_year, fixed::from_f64(reward_price));
+        let staked_value = fixed::mul(staked, fixed::from_f64(stake_price));
+
+        Ok(fixed::to_f64(fixed::percentage(yearly_reward_value, staked_value)))
+    }
+
+    /// APY as a percentage from `getCurrentAPY` when the pool has it, otherwise from `compute_apy` with `oracle`
+    pub async fn apy_with_fallback(&self, oracle: &impl PriceOracle) -> Result<f64, YieldFarmingError> {
+        match self.get_current_apy().await? {
+            Some(apy) => Ok(units::from_wei(apy, 0)),
+            None => self.compute_apy(oracle).await,
+        }
+    }
+
+    /// Sample `getCurrentAPY` at each of `blocks`, returning `(block, apy)` pairs in the order given.
+    ///
+    /// Blocks older than the node's retained state need an archive node.
+    pub async fn apy_history(&self, blocks: &[u64]) -> Result<Vec<(u64, U256)>, YieldFarmingError> {
+        let mut samples: Vec<(usize, u64, U256)> = stream::iter(blocks.iter().copied().enumerate())
+            .map(|(index, block)| async move {
+                let apy = self
+                    .query_at_with_retry("getCurrentAPY", (), Some(BlockNumber::Number(block.into())))
+                    .await?;
+                Ok::<_, YieldFarmingError>((index, block, apy))
+            })
+            .buffer_unordered(APY_HISTORY_CONCURRENCY)
+            .try_collect()
+            .await?;
+
+        samples.sort_by_key(|(index, _, _)| *index);
+        Ok(samples.into_iter().map(|(_, block, apy)| (block, apy)).collect())
+    }
+
+    /// Mean `getCurrentAPY` over the trailing `window_blocks`, sampled at `samples` evenly spaced blocks.
+    ///
+    /// Smooths out the noise of the instantaneous figure; see `apy_stats` for the range too.
+    pub async fn average_apy(&self, window_blocks: u64, samples: usize) -> Result<f64, YieldFarmingError> {
+        Ok(self.apy_stats(window_blocks, samples).await?.mean)
+    }
+
+    /// Mean, minimum and maximum `getCurrentAPY` over the trailing `window_blocks`.
+    ///
+    /// Samples are fetched concurrently through `apy_history`, so windows reaching
+    /// past the node's retained state need an archive node.
+    pub async fn apy_stats(&self, window_blocks: u64, samples: usize) -> Result<ApyStats, YieldFarmingError> {
+        let latest = self.resolve_block_number(BlockNumber::Latest).await?;
+        let history = self.apy_history(&sample_blocks(latest, window_blocks, samples)).await?;
+
+        let values: Vec<f64> = history.iter().map(|(_, apy)| units::from_wei(*apy, 0)).collect();
+        Ok(ApyStats {
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        })
+    }
+
+    /// Get the pool-wide emission rate in reward token base units per second
+    pub async fn reward_rate(&self) -> Result<U256, YieldFarmingError> {
+        self.query_with_retry("rewardRate", ()).await
+    }
+
+    /// Get the Unix timestamp at which the current reward period ends
+    pub async fn period_finish(&self) -> Result<u64, YieldFarmingError> {
+        let finish: U256 = self.query_with_retry("periodFinish", ()).await?;
+        u64::try_from(finish).map_err(|_| YieldFarmingError::ArithmeticOverflow("period finish"))
+    }
+
+    /// Estimate the rewards `account` earns per day at its current share of the pool.
+    ///
+    /// Assumes the reward period is still running; compare against `period_finish`
+    /// for a period that may already have ended.
+    pub async fn rewards_per_day(&self, account: Address) -> Result<U256, YieldFarmingError> {
+        let (reward_rate, total_supply, staked) = futures::try_join!(
+            self.reward_rate(),
+            self.query_with_retry::<U256, _>("totalSupply", ()),
+            self.get_staked_balance(account),
+        )?;
+
+        daily_share(reward_rate, staked, total_supply)
+    }
+
+    /// Project the rewards `account` earns over `horizon` at the current reward rate.
+    ///
+    /// Assumes the account's stake and the pool's total stake stay constant, so
+    /// deposits and withdrawals by others during the horizon are not accounted
+    /// for, and that the reward period runs for the whole horizon.
+    pub async fn forecast_rewards(&self, account: Address, horizon: Duration) -> Result<U256, YieldFarmingError> {
+        let (reward_rate, total_supply, staked) = futures::try_join!(
+            self.reward_rate(),
+            self.query_with_retry::<U256, _>("totalSupply", ()),
+            self.get_staked_balance(account),
+        )?;
+
+        share_over(reward_rate, staked, total_supply, horizon.as_secs())
+    }
+
+    /// Estimate `account`'s APY as a percentage after paying gas to claim `claims_per_year` times.
+    ///
+    /// Gross rewards come from `rewards_per_day`, and claim gas is priced at the
+    /// node's current gas price. `oracle` must price the staking and reward tokens
+    /// and `NATIVE_TOKEN`. The result is negative when gas outweighs the rewards,
+    /// and `0.0` for an empty position.
+    pub async fn net_apy(&self, account: Address, oracle: &impl PriceOracle, claims_per_year: u32) -> Result<f64, YieldFarmingError> {
+        let (staked, daily_rewards, claim_gas, gas_price) = futures::try_join!(
+            self.get_staked_balance(account),
+            self.rewards_per_day(account),
+            self.estimate_claim_rewards_gas(account),
+            retry::with_timeout(self.request_timeout, async { Ok(self.web3.eth().gas_price().await?) }),
+        )?;
+        if staked.is_zero() {
+            return Ok(0.0);
+        }
+
+        let (staking_token, reward_token) = futures::try_join!(self.staking_token(), self.reward_token())?;
+        let (staking_decimals, reward_decimals, stake_price, reward_price, native_price) = futures::try_join!(
+            self.token_decimals(),
+            self.reward_token_decimals(),
+            oracle.price_usd(staking_token),
+            oracle.price_usd(reward_token),
+            oracle.price_usd(NATIVE_TOKEN),
+        )?;
+
+        let yearly_reward_value = units::from_wei(daily_rewards, reward_decimals) * 365.0 * reward_price;
+        let yearly_gas_cost = units::from_wei(claim_gas.saturating_mul(gas_price), 18) * native_price * f64::from(claims_per_year);
+        let position_value = units::from_wei(staked, staking_decimals) * stake_price;
+
+        Ok(net_apy_percentage(yearly_reward_value, yearly_gas_cost, position_value))
+    }
+
+    /// Read ERC20 `decimals()` for an arbitrary token, cached per token
+    pub(crate) async fn erc20_decimals(&self, token: Address) -> Result<u8, YieldFarmingError> {
+        self.view_cache
+            .get_or_fetch(ViewCache::key(token, "decimals", ()), || async {
+                let token_contract = erc20::erc20_contract(self.web3.eth(), token)?;
+                retry::with_timeout(self.request_timeout, async {
+                    let decimals: u8 = token_contract
+                        .query("decimals", (), None, Options::default(), None)
+                        .await?;
+                    Ok(decimals)
+                })
+                .await
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apy_percentage() {
+        // 1 reward token/sec at $0.10 against $10M staked
+        let yearly = units::from_wei(units::ether_to_wei(1.0), 18) * SECONDS_PER_YEAR as f64 * 0.10;
+        let apy = apy_percentage(yearly, 10_000_000.0);
+        assert!((apy - 31.536).abs() < 1e-9);
+        assert_eq!(apy_percentage(1.0, 0.0), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_apy_history_preserves_order() {
+        let transport = crate::testing::MockTransport::new();
+        transport.push_call_result(&[web3::ethabi::Token::Uint(12.into())]);
+        transport.push_call_result(&[web3::ethabi::Token::Uint(15.into())]);
+        let abi = br#"[{"type":"function","name":"getCurrentAPY","stateMutability":"view","inputs":[],"outputs":[{"name":"","type":"uint256"}]}]"#;
+        let client = YieldFarmingClient::from_transport(transport.clone(), Address::zero(), abi).unwrap();
+
+        let history = client.apy_history(&[100, 200]).await.unwrap();
+        assert_eq!(history, vec![(100, U256::from(12)), (200, U256::from(15))]);
+        assert_eq!(transport.requests()[1].1[1], "0xc8");
+    }
+
+    #[tokio::test]
+    async fn test_apy_stats_over_window() {
+        assert_eq!(sample_blocks(1000, 300, 4), vec![700, 800, 900, 1000]);
+        assert_eq!(sample_blocks(100, 300, 3), vec![0, 50, 100]);
+        assert_eq!(sample_blocks(100, 300, 0), vec![100]);
+
+        let transport = crate::testing::MockTransport::new();
+        transport.set_response("eth_blockNumber", serde_json::json!("0x3e8"));
+        for apy in [10, 20, 30] {
+            transport.push_call_result(&[web3::ethabi::Token::Uint(apy.into())]);
+        }
+        let abi = br#"[{"type":"function","name":"getCurrentAPY","stateMutability":"view","inputs":[],"outputs":[{"name":"","type":"uint256"}]}]"#;
+        let client = YieldFarmingClient::from_transport(transport, Address::zero(), abi).unwrap();
+
+        let stats = client.apy_stats(500, 3).await.unwrap();
+        assert_eq!(stats, ApyStats { mean: 20.0, min: 10.0, max: 30.0 });
+    }
+
+    #[tokio::test]
+    async fn test_apy_stats_beyond_u128() {
+        let transport = crate::testing::MockTransport::new();
+        transport.set_response("eth_blockNumber", serde_json::json!("0x3e8"));
+        let huge = U256::from(u128::MAX) * 4;
+        transport.push_call_result(&[web3::ethabi::Token::Uint(huge)]);
+        let abi = br#"[{"type":"function","name":"getCurrentAPY","stateMutability":"view","inputs":[],"outputs":[{"name":"","type":"uint256"}]}]"#;
+        let client = YieldFarmingClient::from_transport(transport, Address::zero(), abi).unwrap();
+
+        // Truncating to the low 128 bits would give u128::MAX - 3
+        let stats = client.apy_stats(500, 0).await.unwrap();
+        assert_eq!(stats.max, u128::MAX as f64 * 4.0);
+    }
+
+    #[tokio::test]
+    async fn test_period_finish_beyond_u64() {
+        let transport = crate::testing::MockTransport::new();
+        transport.push_call_result(&[web3::ethabi::Token::Uint(1_700_000_000u64.into())]);
+        transport.push_call_result(&[web3::ethabi::Token::Uint(U256::from(u64::MAX) + 1)]);
+        let abi = br#"[{"type":"function","name":"periodFinish","stateMutability":"view","inputs":[],"outputs":[{"name":"","type":"uint256"}]}]"#;
+        let client = YieldFarmingClient::from_transport(transport, Address::zero(), abi).unwrap();
+
+        assert_eq!(client.period_finish().await.unwrap(), 1_700_000_000);
+        assert!(matches!(client.period_finish().await, Err(YieldFarmingError::ArithmeticOverflow(_))));
+    }
+
+    #[tokio::test]
+    async fn test_current_apy_absent_from_abi() {
+        let transport = crate::testing::MockTransport::new();
+        let client = YieldFarmingClient::from_transport(transport.clone(), Address::zero(), b"[]").unwrap();
+
+        assert_eq!(client.get_current_apy().await.unwrap(), None);
+        assert!(transport.requests().is_empty());
+    }
+
+    #[test]
+    fn test_daily_share() {
+        // A quarter of the pool at 10 units/sec
+        assert_eq!(daily_share(10.into(), 25.into(), 100.into()).unwrap(), U256::from(216_000));
+        assert_eq!(daily_share(10.into(), 25.into(), U256::zero()).unwrap(), U256::zero());
+        assert_eq!(share_over(10.into(), 25.into(), 100.into(), 7 * SECONDS_PER_DAY).unwrap(), U256::from(7 * 216_000));
+        assert!(matches!(daily_share(U256::MAX / 1000, 1.into(), 1.into()), Err(YieldFarmingError::ArithmeticOverflow(_))));
+    }
+
+    #[test]
+    fn test_net_apy_percentage() {
+        // $100/year of rewards on a $1,000 position, with $40/year of claim gas
+        assert!((net_apy_percentage(100.0, 40.0, 1_000.0) - 6.0).abs() < 1e-9);
+        assert!(net_apy_percentage(100.0, 150.0, 1_000.0) < 0.0);
+    }
+}
@@ -0,0 +1,185 @@
+use futures::{stream, Stream};
+use std::time::Duration;
+use web3::{
+    types::{Address, H256, U256},
+    Transport,
+};
+
+use crate::{
+    retry, telemetry, trace, units, PriceOracle, YieldFarmingClient, YieldFarmingError, DEFAULT_MAX_POLL_ATTEMPTS, MAINNET_BLOCK_TIME, NATIVE_TOKEN,
+};
+
+/// Blocks sampled to measure the block time for `break_even_blocks`
+const BLOCK_TIME_SAMPLE: u64 = 100;
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Claim `account`'s rewards whenever they reach `threshold`, polling every `poll_interval`.
+    ///
+    /// Emits each claim's hash once it is mined. A claim is skipped while its gas
+    /// cost, valued through `oracle` (which must price the reward token and
+    /// `NATIVE_TOKEN`), would exceed the rewards claimed. Failed polls and claims are
+    /// emitted as errors and polling continues.
+    pub fn auto_claim<'a>(
+        &'a self,
+        account: Address,
+        threshold: U256,
+        poll_interval: Duration,
+        oracle: &'a impl PriceOracle,
+    ) -> impl Stream<Item = Result<H256, YieldFarmingError>> + 'a {
+        stream::unfold(true, move |first| async move {
+            if !first {
+                tokio::time::sleep(poll_interval).await;
+            }
+            loop {
+                match self.claim_if_worthwhile(account, threshold, poll_interval, oracle).await {
+                    Ok(None) => tokio::time::sleep(poll_interval).await,
+                    Ok(Some(tx_hash)) => return Some((Ok(tx_hash), false)),
+                    Err(err) => return Some((Err(err), false)),
+                }
+            }
+        })
+    }
+
+    /// One `auto_claim` poll: claim and wait for it to be mined if rewards reached `threshold` and cover the gas
+    async fn claim_if_worthwhile(
+        &self,
+        account: Address,
+        threshold: U256,
+        poll_interval: Duration,
+        oracle: &impl PriceOracle,
+    ) -> Result<Option<H256>, YieldFarmingError> {
+        let pending = self.get_pending_rewards(account).await?;
+        if pending < threshold || pending.is_zero() {
+            return Ok(None);
+        }
+        if !self.claim_covers_gas(account, pending, oracle).await? {
+            trace::debug!(?account, %pending, "skipping claim that would cost more gas than it returns");
+            return Ok(None);
+        }
+
+        let tx_hash = self.claim_rewards(account).await?;
+        // Wait for the claim to land so the next poll doesn't see the same rewards and claim again
+        let receipt = self
+            .wait_for_confirmation(tx_hash, 0, poll_interval, DEFAULT_MAX_POLL_ATTEMPTS)
+            .await?;
+        if !receipt.status {
+            telemetry::tx_reverted();
+            return Err(YieldFarmingError::TransactionReverted(tx_hash));
+        }
+        Ok(Some(tx_hash))
+    }
+
+    /// Blocks of further accrual before `account`'s pending rewards outweigh the gas to claim them.
+    ///
+    /// Returns `None` if claiming already pays off. Values rewards and gas through
+    /// `oracle`, which must price the reward token and `NATIVE_TOKEN`, and assumes
+    /// `rewards_per_day` and today's gas price hold. Fails with `NoRewards` if the
+    /// account isn't accruing anything.
+    pub async fn break_even_blocks(&self, account: Address, oracle: &impl PriceOracle) -> Result<Option<u64>, YieldFarmingError> {
+        let pending = self.get_pending_rewards(account).await?;
+        let (gas_cost, reward_decimals, reward_price) = self.claim_cost(account, oracle).await?;
+        let shortfall = gas_cost - units::from_wei(pending, reward_decimals) * reward_price;
+        if shortfall < 0.0 {
+            return Ok(None);
+        }
+
+        let (daily_rewards, block_time) = futures::try_join!(
+            self.rewards_per_day(account),
+            self.average_block_time(BLOCK_TIME_SAMPLE),
+        )?;
+        let block_time = if block_time.is_zero() { MAINNET_BLOCK_TIME } else { block_time };
+        let per_block = units::from_wei(daily_rewards, reward_decimals) * reward_price * block_time.as_secs_f64() / SECONDS_PER_DAY;
+        blocks_to_cover(shortfall, per_block).map(Some).ok_or(YieldFarmingError::NoRewards)
+    }
+
+    /// Whether `pending` rewards are worth more than the gas to claim them
+    async fn claim_covers_gas(&self, account: Address, pending: U256, oracle: &impl PriceOracle) -> Result<bool, YieldFarmingError> {
+        let (gas_cost, reward_decimals, reward_price) = self.claim_cost(account, oracle).await?;
+        Ok(units::from_wei(pending, reward_decimals) * reward_price > gas_cost)
+    }
+
+    /// USD cost of claiming at the current gas price, with the reward token's decimals and USD price
+    async fn claim_cost(&self, account: Address, oracle: &impl PriceOracle) -> Result<(f64, u8, f64), YieldFarmingError> {
+        let reward_token = self.reward_token().await?;
+        let (claim_gas, gas_price, reward_decimals, reward_price, native_price) = futures::try_join!(
+            self.estimate_claim_rewards_gas(account),
+            retry::with_timeout(self.request_timeout, async { Ok(self.web3.eth().gas_price().await?) }),
+            self.erc20_decimals(reward_token),
+            oracle.price_usd(reward_token),
+            oracle.price_usd(NATIVE_TOKEN),
+        )?;
+        let gas_cost = units::from_wei(claim_gas.saturating_mul(gas_price), 18) * native_price;
+        Ok((gas_cost, reward_decimals, reward_price))
+    }
+}
+
+/// Whole blocks needed to accrue `shortfall` at `per_block`, or `None` if nothing accrues
+fn blocks_to_cover(shortfall: f64, per_block: f64) -> Option<u64> {
+    if per_block <= 0.0 || !per_block.is_finite() {
+        return None;
+    }
+    Some((shortfall / per_block).ceil().max(1.0) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTransport;
+    use futures::StreamExt;
+    use web3::{ethabi::Token, types::TransactionReceipt};
+
+    const POOL_ABI: &[u8] = br#"[
+        {"type":"function","name":"pendingRewards","stateMutability":"view","inputs":[{"name":"user","type":"address"}],"outputs":[{"name":"","type":"uint256"}]},
+        {"type":"function","name":"rewardToken","stateMutability":"view","inputs":[],"outputs":[{"name":"","type":"address"}]},
+        {"type":"function","name":"claimRewards","stateMutability":"nonpayable","inputs":[],"outputs":[]}
+    ]"#;
+
+    /// $1 per whole token, native or not
+    struct FlatOracle;
+
+    impl PriceOracle for FlatOracle {
+        async fn price_usd(&self, _token: Address) -> Result<f64, YieldFarmingError> {
+            Ok(1.0)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_auto_claim_skips_unprofitable_claims() {
+        let transport = MockTransport::new();
+        let tx_hash = H256::repeat_byte(0xcc);
+        let receipt = TransactionReceipt {
+            transaction_hash: tx_hash,
+            block_number: Some(1u64.into()),
+            status: Some(1u64.into()),
+            ..TransactionReceipt::default()
+        };
+        // 1 gwei of rewards is below the 21000 gwei of gas; 1 token is well above it.
+        // The reward token and its decimals are cached after the first poll.
+        transport.push_call_result(&[Token::Uint(U256::exp10(9))]);
+        transport.push_call_result(&[Token::Address(Address::repeat_byte(0x0e))]);
+        transport.push_call_result(&[Token::Uint(18.into())]);
+        transport.push_call_result(&[Token::Uint(U256::exp10(18))]);
+        transport.set_response("eth_estimateGas", serde_json::json!("0x5208"));
+        transport.set_response("eth_gasPrice", serde_json::json!("0x3b9aca00"));
+        transport.set_response("eth_sendTransaction", serde_json::to_value(tx_hash).unwrap());
+        transport.set_response("eth_getTransactionReceipt", serde_json::to_value(&receipt).unwrap());
+        transport.set_response("eth_blockNumber", serde_json::json!("0x1"));
+
+        let client = YieldFarmingClient::from_transport(transport.clone(), Address::zero(), POOL_ABI).unwrap();
+        let claims = client.auto_claim(Address::repeat_byte(0x01), U256::one(), Duration::from_millis(1), &FlatOracle);
+        futures::pin_mut!(claims);
+
+        assert_eq!(claims.next().await.unwrap().unwrap(), tx_hash);
+        let sends = transport.requests().into_iter().filter(|(method, _)| method == "eth_sendTransaction").count();
+        assert_eq!(sends, 1);
+    }
+
+    #[test]
+    fn test_blocks_to_cover() {
+        assert_eq!(blocks_to_cover(1.0, 0.3), Some(4));
+        assert_eq!(blocks_to_cover(0.0, 0.3), Some(1));
+        assert_eq!(blocks_to_cover(1.0, 0.0), None);
+    }
+}
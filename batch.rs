@@ -0,0 +1,76 @@
+use std::ops::Deref;
+
+use crate::YieldFarmingError;
+
+/// Per-item results of a batch operation, in the same order as its inputs.
+///
+/// One failing item, such as an invalid account or a reverting call, doesn't
+/// discard the results of the others.
+#[derive(Debug)]
+pub struct BatchResult<T> {
+    results: Vec<Result<T, YieldFarmingError>>,
+}
+
+impl<T> BatchResult<T> {
+    /// The successful results, dropping failures
+    pub fn into_oks(self) -> Vec<T> {
+        self.results.into_iter().filter_map(Result::ok).collect()
+    }
+
+    /// All results, aligned with the batch's inputs
+    pub fn into_results(self) -> Vec<Result<T, YieldFarmingError>> {
+        self.results
+    }
+
+    /// Whether every item succeeded
+    pub fn all_ok(&self) -> bool {
+        self.results.iter().all(Result::is_ok)
+    }
+
+    /// Apply `f` to each successful result
+    pub(crate) fn map<U>(self, mut f: impl FnMut(T) -> Result<U, YieldFarmingError>) -> BatchResult<U> {
+        self.results.into_iter().map(|result| result.and_then(&mut f)).collect()
+    }
+}
+
+impl<T> Deref for BatchResult<T> {
+    type Target = [Result<T, YieldFarmingError>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.results
+    }
+}
+
+impl<T> From<Vec<Result<T, YieldFarmingError>>> for BatchResult<T> {
+    fn from(results: Vec<Result<T, YieldFarmingError>>) -> Self {
+        Self { results }
+    }
+}
+
+impl<T> FromIterator<Result<T, YieldFarmingError>> for BatchResult<T> {
+    fn from_iter<I: IntoIterator<Item = Result<T, YieldFarmingError>>>(iter: I) -> Self {
+        Self { results: iter.into_iter().collect() }
+    }
+}
+
+impl<T> IntoIterator for BatchResult<T> {
+    type Item = Result<T, YieldFarmingError>;
+    type IntoIter = std::vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.results.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into_oks_skips_failures() {
+        let batch: BatchResult<u32> = vec![Ok(1), Err(YieldFarmingError::ZeroAmount), Ok(3)].into();
+        assert_eq!(batch.len(), 3);
+        assert!(!batch.all_ok());
+        assert_eq!(batch.into_oks(), vec![1, 3]);
+    }
+}
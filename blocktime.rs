@@ -0,0 +1,66 @@
+use std::time::Duration;
+use web3::{
+    types::{BlockId, BlockNumber},
+    Transport,
+};
+
+use crate::{retry, YieldFarmingClient, YieldFarmingError};
+
+/// Block time of Ethereum mainnet since the merge
+pub const MAINNET_BLOCK_TIME: Duration = Duration::from_secs(12);
+
+/// Seconds spanned by `blocks` blocks produced every `average_block_time`
+pub fn blocks_to_seconds(blocks: u64, average_block_time: Duration) -> u64 {
+    (average_block_time.as_secs_f64() * blocks as f64).round() as u64
+}
+
+/// Blocks produced over `seconds` at one block every `average_block_time`, rounded down
+pub fn seconds_to_blocks(seconds: u64, average_block_time: Duration) -> u64 {
+    if average_block_time.is_zero() {
+        return 0;
+    }
+    (seconds as f64 / average_block_time.as_secs_f64()) as u64
+}
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Get the Unix timestamp of `block`
+    pub async fn block_timestamp(&self, block: BlockNumber) -> Result<u64, YieldFarmingError> {
+        let block = self.retry_policy
+            .retry(|| retry::with_timeout(self.request_timeout, async {
+                Ok(self.web3.eth().block(BlockId::Number(block)).await?)
+            }))
+            .await?
+            .ok_or(YieldFarmingError::BlockNotFound)?;
+        Ok(block.timestamp.as_u64())
+    }
+
+    /// Measure the average block time over the last `sample_blocks` blocks.
+    ///
+    /// The sample is capped at the chain's length; a chain with only its genesis block reports zero.
+    pub async fn average_block_time(&self, sample_blocks: u64) -> Result<Duration, YieldFarmingError> {
+        let latest = self.get_latest_block().await?;
+        let sample_blocks = sample_blocks.min(latest);
+        if sample_blocks == 0 {
+            return Ok(Duration::ZERO);
+        }
+        let (newest, oldest) = futures::try_join!(
+            self.block_timestamp(latest.into()),
+            self.block_timestamp((latest - sample_blocks).into()),
+        )?;
+        Ok(Duration::from_secs_f64(newest.saturating_sub(oldest) as f64 / sample_blocks as f64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_duration_conversions() {
+        assert_eq!(blocks_to_seconds(7_200, MAINNET_BLOCK_TIME), 86_400);
+        assert_eq!(blocks_to_seconds(3, Duration::from_millis(250)), 1);
+        assert_eq!(seconds_to_blocks(86_400, MAINNET_BLOCK_TIME), 7_200);
+        assert_eq!(seconds_to_blocks(11, MAINNET_BLOCK_TIME), 0);
+        assert_eq!(seconds_to_blocks(60, Duration::ZERO), 0);
+    }
+}
@@ -0,0 +1,434 @@
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use std::sync::Arc;
+use std::time::Duration;
+use web3::{
+    contract::Contract,
+    error::TransportError,
+    transports::Http,
+    types::{Address, U256},
+    Transport,
+};
+
+use crate::cache::ViewCache;
+use crate::inflight::InFlightGuard;
+use crate::{
+    parse_checked_address, DepositLimits, EventSignatures, FailoverYieldFarmingClient, HttpYieldFarmingClient, MultiTransport, RetryPolicy,
+    StakeQueryStrategy, YieldFarmingClient, YieldFarmingError,
+};
+
+/// Builder for configuring a `YieldFarmingClient`
+#[derive(Debug, Default, Clone)]
+pub struct YieldFarmingClientBuilder {
+    rpc_url: Option<String>,
+    rpc_urls: Vec<String>,
+    failover_cooldown: Option<Duration>,
+    contract_address: Option<Address>,
+    contract_address_str: Option<String>,
+    contract_ens: Option<String>,
+    abi: Option<Vec<u8>>,
+    request_timeout: Option<Duration>,
+    http_headers: Vec<(String, String)>,
+    log_chunk_size: Option<u64>,
+    confirmed_only: u64,
+    expected_chain_id: Option<u64>,
+    retry_policy: Option<RetryPolicy>,
+    multicall_address: Option<Address>,
+    stake_query_strategy: Option<StakeQueryStrategy>,
+    event_signatures: Option<EventSignatures>,
+    min_deposit: Option<U256>,
+    max_deposit: Option<U256>,
+    cache_enabled: Option<bool>,
+    fee_on_transfer: bool,
+    check_capacity: bool,
+    simulate_before_send: bool,
+    debug_rpc: bool,
+    default_account: Option<Address>,
+    dedupe_in_flight: bool,
+}
+
+impl YieldFarmingClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the HTTP RPC endpoint
+    pub fn rpc_url(mut self, rpc_url: &str) -> Self {
+        self.rpc_url = Some(rpc_url.to_string());
+        self
+    }
+
+    /// Set the HTTP RPC endpoints for `build_multi`, highest priority first
+    pub fn rpc_urls(mut self, rpc_urls: &[&str]) -> Self {
+        self.rpc_urls = rpc_urls.iter().map(|url| url.to_string()).collect();
+        self
+    }
+
+    /// How long `build_multi` clients stay on a fallback endpoint before retrying the primary
+    pub fn failover_cooldown(mut self, cooldown: Duration) -> Self {
+        self.failover_cooldown = Some(cooldown);
+        self
+    }
+
+    /// Set the yield farming contract address
+    pub fn contract_address(mut self, contract_address: Address) -> Self {
+        self.contract_address = Some(contract_address);
+        self.contract_address_str = None;
+        self.contract_ens = None;
+        self
+    }
+
+    /// Set the yield farming contract address from a hex string, validated by `parse_checked_address` on `build`
+    pub fn contract_address_str(mut self, contract_address: &str) -> Self {
+        self.contract_address_str = Some(contract_address.to_string());
+        self.contract_address = None;
+        self.contract_ens = None;
+        self
+    }
+
+    /// Set the yield farming contract by ENS name, e.g. `vault.protocol.eth`, resolved by `build_async`
+    pub fn contract_ens(mut self, name: &str) -> Self {
+        self.contract_ens = Some(name.to_string());
+        self.contract_address = None;
+        self.contract_address_str = None;
+        self
+    }
+
+    /// Set the contract ABI from raw JSON bytes
+    pub fn abi_bytes(mut self, abi: &[u8]) -> Self {
+        self.abi = Some(abi.to_vec());
+        self
+    }
+
+    /// Set the contract ABI from a JSON string
+    pub fn abi_json_str(mut self, abi: &str) -> Self {
+        self.abi = Some(abi.as_bytes().to_vec());
+        self
+    }
+
+    /// Set the timeout applied to every RPC request, failing it with `Timeout`
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Send `name: value` with every RPC request, e.g. a provider API key.
+    ///
+    /// May be called repeatedly; names and values are validated by `build`.
+    pub fn http_header(mut self, name: &str, value: &str) -> Self {
+        self.http_headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Set the maximum block span of a single `eth_getLogs` request
+    pub fn log_chunk_size(mut self, blocks: u64) -> Self {
+        self.log_chunk_size = Some(blocks);
+        self
+    }
+
+    /// Only return events buried under at least `confirmations` blocks, from both range
+    /// queries and subscriptions, so an indexer near the chain tip doesn't ingest logs a
+    /// reorg may still drop
+    pub fn confirmed_only(mut self, confirmations: u64) -> Self {
+        self.confirmed_only = confirmations;
+        self
+    }
+
+    /// Set the chain the client must be connected to, checked by `verify_chain`
+    pub fn expected_chain_id(mut self, chain_id: u64) -> Self {
+        self.expected_chain_id = Some(chain_id);
+        self
+    }
+
+    /// Set how read-only queries are retried on transient transport errors
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Set the Multicall3 contract used for batched reads, defaulting to `MULTICALL3_ADDRESS`
+    pub fn multicall_address(mut self, address: Address) -> Self {
+        self.multicall_address = Some(address);
+        self
+    }
+
+    /// Set how staked balances are read, for pools without `balanceOf`
+    pub fn stake_query_strategy(mut self, strategy: StakeQueryStrategy) -> Self {
+        self.stake_query_strategy = Some(strategy);
+        self
+    }
+
+    /// Set the event signatures used to fetch, subscribe to and decode pool events
+    pub fn event_signatures(mut self, signatures: EventSignatures) -> Self {
+        self.event_signatures = Some(signatures);
+        self
+    }
+
+    /// Reject deposits below `amount` before submitting them
+    pub fn min_deposit(mut self, amount: U256) -> Self {
+        self.min_deposit = Some(amount);
+        self
+    }
+
+    /// Reject deposits above `amount` before submitting them
+    pub fn max_deposit(mut self, amount: U256) -> Self {
+        self.max_deposit = Some(amount);
+        self
+    }
+
+    /// Mark the staking token as charging a fee on transfer, so deposits report the amount the pool
+    /// received rather than the nominal amount in its `Deposit` event
+    pub fn fee_on_transfer(mut self, fee_on_transfer: bool) -> Self {
+        self.fee_on_transfer = fee_on_transfer;
+        self
+    }
+
+    /// Check deposits against the pool's remaining capacity before submitting them, rejecting
+    /// those that would overfill a capped pool with `PoolFull` instead of a revert
+    pub fn check_capacity(mut self, enabled: bool) -> Self {
+        self.check_capacity = enabled;
+        self
+    }
+
+    /// Account used by the `*_default` write methods, e.g. `deposit_default`
+    pub fn default_account(mut self, account: Address) -> Self {
+        self.default_account = Some(account);
+        self
+    }
+
+    /// Dry-run every write with `eth_call` and only send it if the dry run succeeds, returning
+    /// `Reverted` with the decoded reason otherwise.
+    ///
+    /// Saves the gas of transactions that would revert at the cost of an extra RPC
+    /// round trip before each send. State can still change between the dry run and
+    /// inclusion, so a transaction that passed may revert on chain.
+    pub fn simulate_before_send(mut self, enabled: bool) -> Self {
+        self.simulate_before_send = enabled;
+        self
+    }
+
+    /// Log every raw JSON-RPC request and response at `trace` level (off by default).
+    ///
+    /// Responses are logged as the node sent them, so `eth_call` return data shows
+    /// up as hex before decoding, which helps pin down ABI mismatches. Requires the
+    /// `tracing` feature; without it nothing is logged.
+    pub fn debug_rpc(mut self, enabled: bool) -> Self {
+        self.debug_rpc = enabled;
+        self
+    }
+
+    /// Enable or disable caching of slowly-changing view results (enabled by default)
+    pub fn cache_enabled(mut self, enabled: bool) -> Self {
+        self.cache_enabled = Some(enabled);
+        self
+    }
+
+    /// Return the pending transaction's hash instead of resubmitting when a deposit, withdrawal
+    /// or claim identical in function, account and amount is still unmined
+    pub fn dedupe_in_flight(mut self, enabled: bool) -> Self {
+        self.dedupe_in_flight = enabled;
+        self
+    }
+
+    /// Validate the configuration and create the client
+    pub fn build(self) -> Result<HttpYieldFarmingClient, YieldFarmingError> {
+        let rpc_url = self.rpc_url.as_deref().ok_or(YieldFarmingError::MissingField("rpc_url"))?;
+        let transport = self.http_transport(rpc_url)?;
+        self.build_with_transport(transport)
+    }
+
+    /// Validate the configuration and create a client that fails over between the `rpc_urls` endpoints.
+    ///
+    /// See `MultiTransport` for which requests are retried on another endpoint.
+    pub fn build_multi(self) -> Result<FailoverYieldFarmingClient, YieldFarmingError> {
+        let endpoints = self
+            .rpc_urls
+            .iter()
+            .map(|url| self.http_transport(url))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut transport = MultiTransport::new(endpoints)?;
+        if let Some(cooldown) = self.failover_cooldown {
+            transport = transport.with_cooldown(cooldown);
+        }
+        self.build_with_transport(transport)
+    }
+
+    fn http_transport(&self, rpc_url: &str) -> Result<Http, YieldFarmingError> {
+        match (self.request_timeout, self.http_headers.is_empty()) {
+            (None, true) => Ok(Http::new(rpc_url)?),
+            (timeout, _) => http_with_client(rpc_url, timeout, &self.http_headers),
+        }
+    }
+
+    fn build_with_transport<T: Transport>(self, transport: T) -> Result<YieldFarmingClient<T>, YieldFarmingError> {
+        let contract_address = match (self.contract_address, &self.contract_address_str) {
+            (Some(address), _) => address,
+            (None, Some(address)) => parse_checked_address(address)?,
+            (None, None) => {
+                return Err(match self.contract_ens {
+                    Some(name) => YieldFarmingError::UnresolvedEnsName(name),
+                    None => YieldFarmingError::MissingField("contract_address"),
+                })
+            }
+        };
+        let abi = self.abi.ok_or(YieldFarmingError::MissingField("abi"))?;
+
+        let mut client = YieldFarmingClient::from_transport(transport, contract_address, &abi)?;
+        if let Some(blocks) = self.log_chunk_size {
+            client.log_chunk_size = blocks;
+        }
+        client.log_confirmations = self.confirmed_only;
+        client.expected_chain_id = self.expected_chain_id;
+        client.request_timeout = self.request_timeout;
+        if let Some(policy) = self.retry_policy {
+            client.retry_policy = policy;
+        }
+        if let Some(address) = self.multicall_address {
+            client.multicall_address = address;
+        }
+        if let Some(strategy) = self.stake_query_strategy {
+            client.stake_query_strategy = strategy;
+        }
+        if let Some(signatures) = self.event_signatures {
+            client.event_signatures = signatures;
+        }
+        client.deposit_limits = DepositLimits {
+            min: self.min_deposit,
+            max: self.max_deposit,
+        };
+        client.fee_on_transfer = self.fee_on_transfer;
+        client.check_capacity = self.check_capacity;
+        client.simulate_before_send = self.simulate_before_send;
+        client.web3.transport().set_enabled(self.debug_rpc);
+        client.default_account = self.default_account;
+        client.in_flight = Arc::new(InFlightGuard::new(self.dedupe_in_flight));
+        if let Some(enabled) = self.cache_enabled {
+            client.view_cache = Arc::new(ViewCache::new(enabled));
+        }
+
+        Ok(client)
+    }
+
+    /// Like `build`, but first resolve a contract set with `contract_ens` through the RPC endpoint
+    pub async fn build_async(self) -> Result<HttpYieldFarmingClient, YieldFarmingError> {
+        let Some(name) = self.contract_ens.clone() else {
+            return self.build();
+        };
+        let mut client = self.contract_address(Address::zero()).build()?;
+        let address = client.resolve_ens(&name).await?;
+        client.contract = Arc::new(Contract::new(client.web3.eth(), address, client.contract.abi().clone()));
+        Ok(client)
+    }
+
+    /// Create the client and confirm the node is on the expected chain
+    pub async fn build_verified(self) -> Result<HttpYieldFarmingClient, YieldFarmingError> {
+        let client = self.build()?;
+        client.verify_chain().await?;
+        Ok(client)
+    }
+}
+
+/// HTTP transport over a `reqwest` client with a request timeout and default headers
+fn http_with_client(rpc_url: &str, timeout: Option<Duration>, headers: &[(String, String)]) -> Result<Http, YieldFarmingError> {
+    let transport_error = |message: String| YieldFarmingError::Transport(web3::Error::Transport(TransportError::Message(message)));
+
+    let mut builder = reqwest::Client::builder()
+        .user_agent("web3.rs")
+        .default_headers(header_map(headers)?);
+    if let Some(timeout) = timeout {
+        builder = builder.timeout(timeout);
+    }
+    let client = builder
+        .build()
+        .map_err(|err| transport_error(format!("failed to build client: {}", err)))?;
+    let url = rpc_url
+        .parse()
+        .map_err(|err| transport_error(format!("invalid RPC URL {}: {}", rpc_url, err)))?;
+
+    Ok(Http::with_client(client, url))
+}
+
+fn header_map(headers: &[(String, String)]) -> Result<HeaderMap, YieldFarmingError> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        let name = HeaderName::from_bytes(name.as_bytes()).map_err(|_| YieldFarmingError::InvalidHeader(name.clone()))?;
+        let mut value = HeaderValue::from_str(value).map_err(|_| YieldFarmingError::InvalidHeader(name.to_string()))?;
+        // Keep API keys out of debug output
+        value.set_sensitive(true);
+        map.append(name, value);
+    }
+    Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_reports_missing_field() {
+        let result = YieldFarmingClientBuilder::new()
+            .rpc_url("https://goerli.infura.io/v3/YOUR_PROJECT_ID")
+            .abi_bytes(b"[]")
+            .build();
+
+        assert!(matches!(result, Err(YieldFarmingError::MissingField("contract_address"))));
+    }
+
+    #[test]
+    fn test_build_multi_requires_endpoints() {
+        let builder = YieldFarmingClientBuilder::new()
+            .contract_address(Address::zero())
+            .abi_bytes(b"[]");
+
+        assert!(matches!(builder.clone().build_multi(), Err(YieldFarmingError::MissingField("rpc_urls"))));
+        assert!(builder
+            .rpc_urls(&["https://eth.llamarpc.com", "https://rpc.ankr.com/eth"])
+            .build_multi()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_build_leaves_ens_to_build_async() {
+        let result = YieldFarmingClientBuilder::new()
+            .rpc_url("https://goerli.infura.io/v3/YOUR_PROJECT_ID")
+            .contract_ens("vault.protocol.eth")
+            .abi_bytes(b"[]")
+            .build();
+
+        assert!(matches!(result, Err(YieldFarmingError::UnresolvedEnsName(name)) if name == "vault.protocol.eth"));
+    }
+
+    #[test]
+    fn test_build_with_timeout() {
+        let result = YieldFarmingClientBuilder::new()
+            .rpc_url("https://goerli.infura.io/v3/YOUR_PROJECT_ID")
+            .contract_address(Address::zero())
+            .abi_json_str("[]")
+            .request_timeout(Duration::from_secs(10))
+            .build();
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_build_rejects_bad_checksum() {
+        let result = YieldFarmingClientBuilder::new()
+            .rpc_url("https://goerli.infura.io/v3/YOUR_PROJECT_ID")
+            .contract_address_str("0x5AAeb6053F3E94C9b9A09f33669435E7Ef1BeAed")
+            .abi_bytes(b"[]")
+            .build();
+
+        assert!(matches!(result, Err(YieldFarmingError::InvalidChecksum(_))));
+    }
+
+    #[test]
+    fn test_build_validates_http_headers() {
+        let builder = YieldFarmingClientBuilder::new()
+            .rpc_url("https://eth-mainnet.g.alchemy.com/v2")
+            .contract_address(Address::zero())
+            .abi_bytes(b"[]");
+
+        assert!(builder.clone().http_header("X-Api-Key", "secret").build().is_ok());
+        let result = builder.http_header("X Api Key", "secret").build();
+        assert!(matches!(result, Err(YieldFarmingError::InvalidHeader(ref name)) if name == "X Api Key"));
+    }
+}
@@ -0,0 +1,190 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use web3::{contract::tokens::Tokenize, ethabi, types::Address, Transport};
+
+use crate::{YieldFarmingClient, YieldFarmingError};
+
+/// How long pool-wide figures such as APY and TVL are reused before being re-read
+pub const VOLATILE_VIEW_TTL: Duration = Duration::from_secs(15);
+
+/// How long configurable pool limits are reused before being re-read
+const LIMIT_VIEW_TTL: Duration = Duration::from_secs(300);
+
+/// How long the event-summed lifetime reward total is reused, as recomputing it rescans the pool's history
+const EVENT_SUM_TTL: Duration = Duration::from_secs(600);
+
+/// Cache key name of the `RewardPaid` sum; not a valid Solidity identifier, so it can't clash with a view
+pub(crate) const REWARD_PAID_SUM: &str = "sum(RewardPaid)";
+
+/// Cache key name of the pool's staking token balance when it stands in for `totalValueLocked`
+pub(crate) const STAKING_TOKEN_TVL: &str = "tvl(balanceOf)";
+
+/// How long ENS resolutions are reused
+const ENS_TTL: Duration = Duration::from_secs(3600);
+
+/// Cache key name of ENS forward resolutions, keyed on the registry and the name's namehash
+pub(crate) const ENS_RESOLUTION: &str = "ens(addr)";
+
+/// Contract, view function and ABI-encoded arguments of a cached call
+type CacheKey = (Address, String, Vec<u8>);
+
+#[derive(Debug)]
+struct CacheEntry {
+    value: Arc<dyn Any + Send + Sync>,
+    /// `None` for values that never change
+    expires_at: Option<Instant>,
+}
+
+/// Read-through cache for view calls whose results rarely or never change
+#[derive(Debug)]
+pub(crate) struct ViewCache {
+    enabled: bool,
+    entries: Mutex<HashMap<CacheKey, CacheEntry>>,
+}
+
+impl Default for ViewCache {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+impl ViewCache {
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The cache key of calling `function` on `contract` with `params`
+    pub(crate) fn key(contract: Address, function: &str, params: impl Tokenize) -> CacheKey {
+        (contract, function.to_string(), ethabi::encode(&params.into_tokens()))
+    }
+
+    /// Return the cached result for `key`, or run `fetch` and cache its result according to the function's TTL
+    pub(crate) async fn get_or_fetch<R, Fut>(&self, key: CacheKey, fetch: impl FnOnce() -> Fut) -> Result<R, YieldFarmingError>
+    where
+        R: Clone + Send + Sync + 'static,
+        Fut: Future<Output = Result<R, YieldFarmingError>>,
+    {
+        let ttl = match ttl_for(&key.1) {
+            Some(ttl) if self.enabled => ttl,
+            _ => return fetch().await,
+        };
+
+        if let Some(value) = self.lookup::<R>(&key) {
+            return Ok(value);
+        }
+
+        let value = fetch().await?;
+        let entry = CacheEntry {
+            value: Arc::new(value.clone()),
+            expires_at: ttl.map(|ttl| Instant::now() + ttl),
+        };
+        self.lock().insert(key, entry);
+        Ok(value)
+    }
+
+    pub(crate) fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lookup<R: Clone + 'static>(&self, key: &CacheKey) -> Option<R> {
+        let entries = self.lock();
+        let entry = entries.get(key)?;
+        if entry.expires_at.is_some_and(|expires_at| expires_at <= Instant::now()) {
+            return None;
+        }
+        entry.value.downcast_ref::<R>().cloned()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<CacheKey, CacheEntry>> {
+        self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Whether and for how long results of `function` are cached: `Some(None)` caches forever
+fn ttl_for(function: &str) -> Option<Option<Duration>> {
+    match function {
+        "stakingToken" | "rewardToken" | "decimals" | "symbol" => Some(None),
+        "getCurrentAPY" | "totalValueLocked" | STAKING_TOKEN_TVL => Some(Some(VOLATILE_VIEW_TTL)),
+        "minStake" | "maxStake" | "cap" | "maxTotalStake" => Some(Some(LIMIT_VIEW_TTL)),
+        REWARD_PAID_SUM => Some(Some(EVENT_SUM_TTL)),
+        ENS_RESOLUTION => Some(Some(ENS_TTL)),
+        _ => None,
+    }
+}
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Drop all cached view results so the next calls read fresh values
+    pub fn clear_cache(&self) {
+        self.view_cache.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTransport;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use web3::ethabi::Token;
+
+    const POOL_ABI: &[u8] = br#"[
+        {"type":"function","name":"stakingToken","stateMutability":"view","inputs":[],"outputs":[{"name":"","type":"address"}]}
+    ]"#;
+
+    #[tokio::test]
+    async fn test_caches_immutable_views_only() {
+        let cache = ViewCache::default();
+        let fetches = AtomicUsize::new(0);
+        let fetch = || async {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            Ok(18u8)
+        };
+
+        for _ in 0..2 {
+            let decimals = cache.get_or_fetch(ViewCache::key(Address::zero(), "decimals", ()), fetch).await.unwrap();
+            assert_eq!(decimals, 18);
+        }
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+        cache.get_or_fetch(ViewCache::key(Address::zero(), "balanceOf", ()), fetch).await.unwrap();
+        cache.get_or_fetch(ViewCache::key(Address::zero(), "balanceOf", ()), fetch).await.unwrap();
+        assert_eq!(fetches.load(Ordering::SeqCst), 3);
+
+        cache.clear();
+        cache.get_or_fetch(ViewCache::key(Address::zero(), "decimals", ()), fetch).await.unwrap();
+        assert_eq!(fetches.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_cache_always_fetches() {
+        let cache = ViewCache::new(false);
+        let fetches = AtomicUsize::new(0);
+        for _ in 0..2 {
+            cache
+                .get_or_fetch(ViewCache::key(Address::zero(), "decimals", ()), || async {
+                    fetches.fetch_add(1, Ordering::SeqCst);
+                    Ok(6u8)
+                })
+                .await
+                .unwrap();
+        }
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_token_decimals_fetched_once() {
+        let transport = MockTransport::new();
+        transport.push_call_result(&[Token::Address(Address::repeat_byte(0x22))]);
+        transport.push_call_result(&[Token::Uint(6.into())]);
+
+        let client = YieldFarmingClient::from_transport(transport.clone(), Address::repeat_byte(0x11), POOL_ABI).unwrap();
+        assert_eq!(client.token_decimals().await.unwrap(), 6);
+        assert_eq!(client.token_decimals().await.unwrap(), 6);
+        assert_eq!(transport.requests().len(), 2);
+    }
+}
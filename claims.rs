@@ -0,0 +1,109 @@
+use futures::{stream, StreamExt, TryStreamExt};
+use std::collections::HashMap;
+use web3::{
+    types::{Address, BlockNumber, H256, U256},
+    Transport,
+};
+
+use crate::events::{self, CLAIM_REWARDS_EVENT_SIGNATURE};
+use crate::{YieldFarmingClient, YieldFarmingError};
+
+/// Maximum number of block timestamp lookups in flight at once
+const TIMESTAMP_CONCURRENCY: usize = 8;
+
+/// A reward claim made by a user
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClaimEvent {
+    pub amount: U256,
+    pub block_number: u64,
+    /// Unix timestamp of `block_number`
+    pub timestamp: u64,
+    pub tx_hash: H256,
+}
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Fetch every reward claim by `account` between two blocks (inclusive), oldest first.
+    ///
+    /// Reads the pool's `RewardPaid` event (or its configured replacement) and
+    /// `ClaimRewards`, chunked by `log_chunk_size`. Each distinct block's
+    /// timestamp costs one extra request.
+    pub async fn get_claims(&self, account: Address, from: BlockNumber, to: BlockNumber) -> Result<Vec<ClaimEvent>, YieldFarmingError> {
+        let topics = vec![
+            self.event_signatures.reward_paid_topic(),
+            events::event_topic(CLAIM_REWARDS_EVENT_SIGNATURE),
+        ];
+        let logs = self.get_logs_in_range(topics, Some(account), from, to, self.request_timeout).await?;
+        let claims = logs
+            .into_iter()
+            .map(events::UserAmountEvent::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut blocks: Vec<u64> = claims.iter().map(|claim| claim.block_number).collect();
+        blocks.dedup();
+        let timestamps: HashMap<u64, u64> = stream::iter(blocks)
+            .map(|block| async move { Ok::<_, YieldFarmingError>((block, self.block_timestamp(block.into()).await?)) })
+            .buffer_unordered(TIMESTAMP_CONCURRENCY)
+            .try_collect()
+            .await?;
+
+        Ok(claims
+            .into_iter()
+            .map(|claim| ClaimEvent {
+                amount: claim.amount,
+                block_number: claim.block_number,
+                timestamp: timestamps[&claim.block_number],
+                tx_hash: claim.tx_hash,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTransport;
+    use web3::types::{Block, Bytes, Log};
+
+    fn claim_log(signature: &str, block_number: u64, amount: u64) -> Log {
+        let mut data = [0u8; 32];
+        U256::from(amount).to_big_endian(&mut data);
+        Log {
+            address: Address::zero(),
+            topics: vec![events::event_topic(signature), H256::from(Address::repeat_byte(0x01))],
+            data: Bytes(data.to_vec()),
+            block_hash: None,
+            block_number: Some(block_number.into()),
+            transaction_hash: Some(H256::from_low_u64_be(block_number)),
+            transaction_index: None,
+            log_index: Some(0.into()),
+            transaction_log_index: None,
+            log_type: None,
+            removed: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_claims_filters_by_user_and_adds_timestamps() {
+        let transport = MockTransport::new();
+        let logs = vec![
+            claim_log(CLAIM_REWARDS_EVENT_SIGNATURE, 20, 7),
+            claim_log(events::REWARD_PAID_EVENT_SIGNATURE, 10, 5),
+        ];
+        transport.set_response("eth_getLogs", serde_json::to_value(&logs).unwrap());
+        let block = Block::<H256> { timestamp: 1_700_000_000.into(), ..Block::default() };
+        transport.set_response("eth_getBlockByNumber", serde_json::to_value(&block).unwrap());
+
+        let client = YieldFarmingClient::from_transport(transport.clone(), Address::zero(), b"[]").unwrap();
+        let claims = client
+            .get_claims(Address::repeat_byte(0x01), BlockNumber::Number(0.into()), BlockNumber::Number(100.into()))
+            .await
+            .unwrap();
+
+        assert_eq!(claims.iter().map(|claim| claim.block_number).collect::<Vec<_>>(), vec![10, 20]);
+        assert_eq!(claims[1].amount, U256::from(7));
+        assert_eq!(claims[0].timestamp, 1_700_000_000);
+
+        let (_, params) = &transport.requests()[0];
+        assert_eq!(params[0]["topics"][1], serde_json::to_value(H256::from(Address::repeat_byte(0x01))).unwrap());
+    }
+}
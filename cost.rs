@@ -0,0 +1,111 @@
+use serde::Serialize;
+use web3::{
+    types::{BlockId, Transaction, TransactionId, TransactionReceipt, U256},
+    Transport,
+};
+
+use crate::snapshot::serialize_u256;
+use crate::{retry, units, YieldFarmingClient, YieldFarmingError};
+
+/// What a mined transaction cost, from `transaction_cost`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CostReport {
+    #[serde(serialize_with = "serialize_u256")]
+    pub gas_used: U256,
+    /// Price paid per unit of gas, base fee plus priority fee on EIP-1559 chains
+    #[serde(serialize_with = "serialize_u256")]
+    pub effective_gas_price: U256,
+    #[serde(serialize_with = "serialize_u256")]
+    pub cost_wei: U256,
+    pub cost_eth: f64,
+    /// `None` when no ETH price was given
+    pub cost_usd: Option<f64>,
+}
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Work out what a mined transaction cost in wei, ETH and, given `eth_price` in USD, dollars.
+    ///
+    /// Uses the receipt's `effectiveGasPrice` when the node reports it. Older
+    /// nodes omit it, so the price is then derived from the transaction: its
+    /// `gasPrice` for legacy transactions, or the block's base fee plus the
+    /// priority fee, capped at `maxFeePerGas`, for EIP-1559 ones.
+    pub async fn transaction_cost(&self, receipt: &TransactionReceipt, eth_price: Option<f64>) -> Result<CostReport, YieldFarmingError> {
+        let gas_used = receipt.gas_used.ok_or(YieldFarmingError::ReceiptNotFound(receipt.transaction_hash))?;
+        let effective_gas_price = match receipt.effective_gas_price {
+            Some(price) => price,
+            None => self.derive_gas_price(receipt).await?,
+        };
+
+        let cost_wei = gas_used.checked_mul(effective_gas_price).ok_or(YieldFarmingError::ArithmeticOverflow("transaction cost"))?;
+        let cost_eth = units::wei_to_ether(cost_wei);
+        Ok(CostReport {
+            gas_used,
+            effective_gas_price,
+            cost_wei,
+            cost_eth,
+            cost_usd: eth_price.map(|price| cost_eth * price),
+        })
+    }
+
+    /// Effective gas price of a receipt's transaction, for receipts without `effectiveGasPrice`
+    async fn derive_gas_price(&self, receipt: &TransactionReceipt) -> Result<U256, YieldFarmingError> {
+        let tx_hash = receipt.transaction_hash;
+        let transaction = retry::with_timeout(self.request_timeout, async {
+            Ok(self.web3.eth().transaction(TransactionId::Hash(tx_hash)).await?)
+        })
+        .await?
+        .ok_or(YieldFarmingError::TransactionNotFound(tx_hash))?;
+        if transaction.max_fee_per_gas.is_none() {
+            return transaction.gas_price.ok_or(YieldFarmingError::TransactionNotFound(tx_hash));
+        }
+
+        let block_hash = receipt.block_hash.ok_or(YieldFarmingError::BlockNotFound)?;
+        let block = retry::with_timeout(self.request_timeout, async {
+            Ok(self.web3.eth().block(BlockId::Hash(block_hash)).await?)
+        })
+        .await?
+        .ok_or(YieldFarmingError::BlockNotFound)?;
+        dynamic_fee_price(&transaction, block.base_fee_per_gas.unwrap_or_default())
+    }
+}
+
+/// `min(maxFeePerGas, base_fee + maxPriorityFeePerGas)` of an EIP-1559 transaction
+fn dynamic_fee_price(transaction: &Transaction, base_fee: U256) -> Result<U256, YieldFarmingError> {
+    let max_fee = transaction.max_fee_per_gas.unwrap_or_default();
+    let tip = transaction.max_priority_fee_per_gas.unwrap_or_default();
+    let price = base_fee.checked_add(tip).ok_or(YieldFarmingError::ArithmeticOverflow("effective gas price"))?;
+    Ok(price.min(max_fee))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTransport;
+    use web3::types::Address;
+
+    #[test]
+    fn test_dynamic_fee_price_capped_at_max_fee() {
+        let transaction = Transaction {
+            max_fee_per_gas: Some(30.into()),
+            max_priority_fee_per_gas: Some(2.into()),
+            ..Transaction::default()
+        };
+        assert_eq!(dynamic_fee_price(&transaction, 10.into()).unwrap(), U256::from(12));
+        assert_eq!(dynamic_fee_price(&transaction, 29.into()).unwrap(), U256::from(30));
+    }
+
+    #[tokio::test]
+    async fn test_transaction_cost_from_receipt_price() {
+        let client = YieldFarmingClient::from_transport(MockTransport::new(), Address::zero(), b"[]").unwrap();
+        let receipt = TransactionReceipt {
+            gas_used: Some(21_000u64.into()),
+            effective_gas_price: Some(U256::exp10(10)),
+            ..TransactionReceipt::default()
+        };
+
+        let report = client.transaction_cost(&receipt, Some(2_000.0)).await.unwrap();
+        assert_eq!(report.cost_wei, U256::from(210_000_000_000_000u64));
+        assert!((report.cost_eth - 0.00021).abs() < 1e-12);
+        assert!((report.cost_usd.unwrap() - 0.42).abs() < 1e-9);
+    }
+}
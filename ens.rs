@@ -0,0 +1,126 @@
+use std::time::Duration;
+
+use web3::{
+    api::Eth,
+    contract::{Contract, Options},
+    ethabi::Contract as ContractABI,
+    signing::keccak256,
+    types::{Address, H160, H256},
+    Transport,
+};
+
+use crate::cache::{ViewCache, ENS_RESOLUTION};
+use crate::{retry, YieldFarmingClient, YieldFarmingError};
+
+/// ENS registry, deployed at the same address on mainnet and its testnets
+pub const ENS_REGISTRY_ADDRESS: &str = "0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e";
+
+/// The registry's `resolver` and a resolver's `addr`, the only ENS functions forward resolution needs
+const ENS_ABI: &str = r#"[
+    {"type":"function","name":"resolver","stateMutability":"view","inputs":[{"name":"node","type":"bytes32"}],"outputs":[{"name":"","type":"address"}]},
+    {"type":"function","name":"addr","stateMutability":"view","inputs":[{"name":"node","type":"bytes32"}],"outputs":[{"name":"","type":"address"}]}
+]"#;
+
+/// `ENS_REGISTRY_ADDRESS` as an `Address`
+fn ens_registry_address() -> Address {
+    H160([
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x0c, 0x2e, 0x07, 0x4e, 0xc6, 0x9a, 0x0d, 0xfb, 0x29, 0x97, 0xba, 0x6c, 0x7d, 0x2e, 0x1e,
+    ])
+}
+
+/// EIP-137 namehash of `name`.
+///
+/// Labels are only lowercased, not fully UTS-46 normalized, which covers plain ASCII names.
+pub fn namehash(name: &str) -> H256 {
+    let node = name
+        .to_lowercase()
+        .split('.')
+        .rev()
+        .filter(|label| !label.is_empty())
+        .fold([0u8; 32], |node, label| {
+            let mut preimage = [0u8; 64];
+            preimage[..32].copy_from_slice(&node);
+            preimage[32..].copy_from_slice(&keccak256(label.as_bytes()));
+            keccak256(&preimage)
+        });
+    H256(node)
+}
+
+/// Look up the address `name` points to through the ENS registry, bounding each call by `timeout`
+pub(crate) async fn resolve<T: Transport>(eth: Eth<T>, name: &str, timeout: Option<Duration>) -> Result<Address, YieldFarmingError> {
+    let abi = ContractABI::load(ENS_ABI.as_bytes())?;
+    let node = namehash(name);
+    let not_found = || YieldFarmingError::EnsNameNotFound(name.to_string());
+
+    let registry = Contract::new(eth.clone(), ens_registry_address(), abi.clone());
+    let resolver: Address = retry::with_timeout(timeout, async {
+        Ok(registry.query("resolver", (node,), None, Options::default(), None).await?)
+    })
+    .await?;
+    if resolver.is_zero() {
+        return Err(not_found());
+    }
+
+    let resolver = Contract::new(eth, resolver, abi);
+    let address: Address = retry::with_timeout(timeout, async {
+        Ok(resolver.query("addr", (node,), None, Options::default(), None).await?)
+    })
+    .await?;
+    if address.is_zero() {
+        return Err(not_found());
+    }
+    Ok(address)
+}
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Resolve an ENS name such as `vault.protocol.eth` to the address it points to.
+    ///
+    /// Fails with `EnsNameNotFound` if the name has no resolver or no address set.
+    /// Resolutions are cached for an hour.
+    pub async fn resolve_ens(&self, name: &str) -> Result<Address, YieldFarmingError> {
+        let key = ViewCache::key(ens_registry_address(), ENS_RESOLUTION, (namehash(name),));
+        self.view_cache
+            .get_or_fetch(key, || {
+                self.retry_policy
+                    .retry(|| resolve(self.web3.eth(), name, self.request_timeout))
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTransport;
+    use std::str::FromStr;
+    use web3::ethabi::Token;
+
+    #[test]
+    fn test_namehash() {
+        assert_eq!(namehash(""), H256::zero());
+        assert_eq!(
+            namehash("eth"),
+            H256::from_str("93cdeb708b7545dc668eb9280176169d1c33cfd8ed6f04690a0bcc88a93fc4ae").unwrap()
+        );
+        assert_eq!(
+            namehash("Foo.eth"),
+            H256::from_str("de9b09fd7c5f901e23a3f19fecc54828e9c848539801e86591bd9801b019f84f").unwrap()
+        );
+        assert_eq!(ens_registry_address(), Address::from_str(ENS_REGISTRY_ADDRESS).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_ens_not_found_and_cached() {
+        let transport = MockTransport::new();
+        let target = Address::repeat_byte(0x42);
+        transport.push_call_result(&[Token::Address(Address::zero())]);
+        transport.push_call_result(&[Token::Address(Address::repeat_byte(0x01))]);
+        transport.push_call_result(&[Token::Address(target)]);
+
+        let client = YieldFarmingClient::from_transport(transport.clone(), Address::zero(), b"[]").unwrap();
+        assert!(matches!(client.resolve_ens("missing.eth").await, Err(YieldFarmingError::EnsNameNotFound(name)) if name == "missing.eth"));
+        assert_eq!(client.resolve_ens("vault.eth").await.unwrap(), target);
+        assert_eq!(client.resolve_ens("vault.eth").await.unwrap(), target);
+        assert_eq!(transport.requests().len(), 3);
+    }
+}
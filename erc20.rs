@@ -0,0 +1,36 @@
+use web3::{api::Eth, contract::Contract, ethabi::Contract as ContractABI, types::Address, Transport};
+
+use crate::YieldFarmingError;
+
+/// Minimal ERC20 ABI covering the functions the client needs
+const ERC20_ABI: &str = r#"[
+    {"type":"function","name":"DOMAIN_SEPARATOR","stateMutability":"view","inputs":[],"outputs":[{"name":"","type":"bytes32"}]},
+    {"type":"function","name":"allowance","stateMutability":"view","inputs":[{"name":"owner","type":"address"},{"name":"spender","type":"address"}],"outputs":[{"name":"","type":"uint256"}]},
+    {"type":"function","name":"approve","stateMutability":"nonpayable","inputs":[{"name":"spender","type":"address"},{"name":"amount","type":"uint256"}],"outputs":[{"name":"","type":"bool"}]},
+    {"type":"function","name":"balanceOf","stateMutability":"view","inputs":[{"name":"account","type":"address"}],"outputs":[{"name":"","type":"uint256"}]},
+    {"type":"function","name":"decimals","stateMutability":"view","inputs":[],"outputs":[{"name":"","type":"uint8"}]},
+    {"type":"function","name":"nonces","stateMutability":"view","inputs":[{"name":"owner","type":"address"}],"outputs":[{"name":"","type":"uint256"}]},
+    {"type":"function","name":"symbol","stateMutability":"view","inputs":[],"outputs":[{"name":"","type":"string"}]}
+]"#;
+
+/// Create a contract instance for an ERC20 token
+pub(crate) fn erc20_contract<T: Transport>(eth: Eth<T>, token: Address) -> Result<Contract<T>, YieldFarmingError> {
+    Ok(Contract::new(eth, token, erc20_abi()?))
+}
+
+/// Parsed ERC20 ABI, for encoding calls without a connection
+pub(crate) fn erc20_abi() -> Result<ContractABI, YieldFarmingError> {
+    Ok(ContractABI::load(ERC20_ABI.as_bytes())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_erc20_abi_parses() {
+        let abi = web3::ethabi::Contract::load(ERC20_ABI.as_bytes()).unwrap();
+        assert!(abi.function("allowance").is_ok());
+        assert!(abi.function("approve").is_ok());
+    }
+}
@@ -0,0 +1,277 @@
+use std::path::PathBuf;
+use std::time::Duration;
+use thiserror::Error;
+use web3::{
+    ethabi,
+    types::{Address, BlockNumber, H256, U256},
+};
+
+/// Errors returned by the yield farming client
+#[derive(Debug, Error)]
+pub enum YieldFarmingError {
+    /// The RPC transport or node returned an error
+    #[error("transport error: {0}")]
+    Transport(#[from] web3::Error),
+
+    /// A contract call or query failed
+    #[error("contract error: {0}")]
+    Contract(#[from] web3::contract::Error),
+
+    /// The contract ABI could not be parsed
+    #[error("failed to parse contract ABI: {0}")]
+    AbiParse(#[from] ethabi::Error),
+
+    /// An ABI file could not be read or is not a valid JSON ABI
+    #[error("failed to load ABI from {}: {reason}", path.display())]
+    AbiFile { path: PathBuf, reason: String },
+
+    /// No receipt is available for the transaction yet
+    #[error("transaction receipt not found for {0:?}")]
+    ReceiptNotFound(H256),
+
+    /// The transaction was mined but reverted
+    #[error("transaction {0:?} reverted")]
+    TransactionReverted(H256),
+
+    /// The transaction was replaced by another one with the same nonce and will never be mined
+    #[error("transaction {0:?} was dropped or replaced")]
+    TransactionDropped(H256),
+
+    /// The transaction was not mined before its `valid_until` deadline, so a cancellation was sent
+    #[error("transaction {0:?} missed its deadline; a cancellation was sent")]
+    Expired(H256),
+
+    /// The node does not know the transaction
+    #[error("transaction {0:?} not found")]
+    TransactionNotFound(H256),
+
+    /// The transaction has already been mined and can no longer be replaced
+    #[error("transaction {0:?} is already mined")]
+    AlreadyMined(H256),
+
+    /// A replacement transaction's fees are not at least 10% above the original's
+    #[error("replacement fees must be at least 10% higher than the original transaction's")]
+    ReplacementUnderpriced,
+
+    /// The requested block does not exist
+    #[error("block not found")]
+    BlockNotFound,
+
+    /// The node returned a pending block or receipt that has no block number yet
+    #[error("block is not yet finalized")]
+    BlockNotFinalized,
+
+    /// An address string could not be parsed
+    #[error("invalid address: {0}")]
+    InvalidAddress(String),
+
+    /// An amount string is not a valid decimal for the token
+    #[error("invalid amount: {0}")]
+    InvalidAmount(String),
+
+    /// A mixed-case address string does not match its EIP-55 checksum
+    #[error("address checksum mismatch: {0}")]
+    InvalidChecksum(String),
+
+    /// The node is connected to a different chain than configured
+    #[error("chain id mismatch: expected {expected}, node reports {actual}")]
+    ChainIdMismatch { expected: u64, actual: u64 },
+
+    /// A private key could not be parsed
+    #[error("invalid private key")]
+    InvalidPrivateKey,
+
+    /// The signer's address differs from the account the transaction is sent from
+    #[error("signer address {actual:?} does not match sending account {expected:?}")]
+    SignerMismatch { expected: Address, actual: Address },
+
+    /// No price feed is configured for the token
+    #[error("no price feed configured for token {0:?}")]
+    NoPriceFeed(Address),
+
+    /// A price feed returned an unusable value
+    #[error("invalid price reported for token {0:?}")]
+    InvalidPrice(Address),
+
+    /// A log could not be decoded into an event
+    #[error("invalid log: {0}")]
+    InvalidLog(String),
+
+    /// The operation did not complete in the allotted time
+    #[error("operation timed out")]
+    Timeout,
+
+    /// The pool is paused and would reject the transaction
+    #[error("pool is paused")]
+    PoolPaused,
+
+    /// Rewards can't be redeposited because they are paid in a different token
+    #[error("reward token differs from staking token; rewards cannot be compounded")]
+    CompoundUnsupported,
+
+    /// The pool has no function accepting a permit with the deposit
+    #[error("pool does not support permit deposits")]
+    PermitUnsupported,
+
+    /// There were no rewards to act on
+    #[error("no rewards received")]
+    NoRewards,
+
+    /// A deposit is smaller than the pool's minimum
+    #[error("amount {amount} is below the minimum deposit of {min}")]
+    AmountBelowMinimum { amount: U256, min: U256 },
+
+    /// A deposit is larger than the pool's per-transaction cap
+    #[error("amount {amount} is above the maximum deposit of {max}")]
+    AmountAboveMaximum { amount: U256, max: U256 },
+
+    /// Amount arithmetic overflowed `U256`, naming the quantity being computed
+    #[error("arithmetic overflow computing {0}")]
+    ArithmeticOverflow(&'static str),
+
+    /// Two token amounts with different decimals were combined
+    #[error("cannot combine amounts with {left} and {right} decimals")]
+    DecimalsMismatch { left: u8, right: u8 },
+
+    /// A deposit would push the pool's total stake past its cap
+    #[error("pool is full: deposit of {amount} exceeds the remaining capacity of {remaining}")]
+    PoolFull { amount: U256, remaining: U256 },
+
+    /// A zero amount was passed where a positive one is required
+    #[error("amount must be greater than zero")]
+    ZeroAmount,
+
+    /// A deposit named the depositing account as its own referrer
+    #[error("{0:?} cannot refer itself")]
+    SelfReferral(Address),
+
+    /// The account has no stake to withdraw
+    #[error("nothing staked")]
+    NothingStaked,
+
+    /// A deposit would mint fewer shares than the caller's minimum
+    #[error("deposit would mint {expected} shares, below the minimum of {min}")]
+    SlippageExceeded { expected: U256, min: U256 },
+
+    /// The pool's ABI has no function with this name
+    #[error("function {0} not found in the contract ABI")]
+    UnknownFunction(String),
+
+    /// The number of arguments matches none of the function's overloads
+    #[error("{function} takes {expected} arguments, {actual} given")]
+    ArgumentCountMismatch { function: String, expected: usize, actual: usize },
+
+    /// An HTTP header name or value is not valid
+    #[error("invalid HTTP header: {0}")]
+    InvalidHeader(String),
+
+    /// A withdrawal would return fewer underlying tokens than the caller's minimum
+    #[error("withdrawal would return {expected} underlying tokens, below the minimum of {min}")]
+    UnderlyingBelowMinimum { expected: U256, min: U256 },
+
+    /// `deposit_when`'s condition never held before its timeout
+    #[error("condition not met within {0:?}")]
+    ConditionNotMet(Duration),
+
+    /// An ENS name has no resolver or no address set
+    #[error("ENS name {0} not found")]
+    EnsNameNotFound(String),
+
+    /// The builder was given an ENS name for the contract, which only `build_async` can resolve
+    #[error("ENS name {0} must be resolved with build_async")]
+    UnresolvedEnsName(String),
+
+    /// A `*_default` method was called on a client built without a default account
+    #[error("no default account configured")]
+    NoDefaultAccount,
+
+    /// A required builder field was not set
+    #[error("missing required configuration: {0}")]
+    MissingField(&'static str),
+
+    /// The node no longer holds state for the requested block
+    #[error("state at block {0:?} is unavailable; querying it requires an archive node")]
+    StateUnavailable(BlockNumber),
+
+    /// The node reported that the call would revert
+    #[error("execution reverted: {}", reason.as_deref().unwrap_or("no reason given"))]
+    Reverted { reason: Option<String> },
+
+    /// The call reverted with a custom error declared in the pool's ABI
+    #[error("execution reverted: {name}({})", crate::revert::format_params(params))]
+    CustomError { name: String, params: Vec<ethabi::Token> },
+}
+
+impl YieldFarmingError {
+    /// Convert a contract error, surfacing node-reported reverts as `Reverted`
+    pub(crate) fn from_contract_call(err: web3::contract::Error) -> Self {
+        match err {
+            web3::contract::Error::Api(err) => Self::from_rpc_call(err),
+            other => YieldFarmingError::Contract(other),
+        }
+    }
+
+    /// Convert a node error, decoding the revert reason when the call reverted
+    pub(crate) fn from_rpc_call(err: web3::Error) -> Self {
+        match crate::revert::revert_reason(&err) {
+            Some(reason) => YieldFarmingError::Reverted { reason },
+            None => YieldFarmingError::Contract(web3::contract::Error::Api(err)),
+        }
+    }
+}
+
+/// Whether a query failed because the node has pruned the state it needed
+pub(crate) fn is_missing_state(err: &web3::contract::Error) -> bool {
+    match err {
+        web3::contract::Error::Api(web3::Error::Rpc(rpc)) => {
+            let message = rpc.message.to_lowercase();
+            ["missing trie node", "header not found", "historical state", "pruned"]
+                .iter()
+                .any(|pattern| message.contains(pattern))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rpc_error(message: &str) -> web3::contract::Error {
+        rpc_error_with_data(message, serde_json::Value::Null)
+    }
+
+    fn rpc_error_with_data(message: &str, data: serde_json::Value) -> web3::contract::Error {
+        let rpc = serde_json::from_value(serde_json::json!({ "code": 3, "message": message, "data": data })).unwrap();
+        web3::contract::Error::Api(web3::Error::Rpc(rpc))
+    }
+
+    #[test]
+    fn test_revert_reason_from_rpc_message() {
+        let err = YieldFarmingError::from_contract_call(rpc_error("execution reverted: Pausable: paused"));
+        assert!(matches!(err, YieldFarmingError::Reverted { reason: Some(ref r) } if r == "Pausable: paused"));
+
+        let err = YieldFarmingError::from_contract_call(rpc_error("execution reverted"));
+        assert!(matches!(err, YieldFarmingError::Reverted { reason: None }));
+
+        let err = YieldFarmingError::from_contract_call(rpc_error("insufficient funds for gas"));
+        assert!(matches!(err, YieldFarmingError::Contract(_)));
+    }
+
+    #[test]
+    fn test_missing_state() {
+        assert!(is_missing_state(&rpc_error("missing trie node 1ab2c3 (path ) state 0x1ab2c3 is not available")));
+        assert!(is_missing_state(&rpc_error("header not found")));
+        assert!(!is_missing_state(&rpc_error("execution reverted")));
+    }
+
+    #[test]
+    fn test_revert_reason_from_rpc_data() {
+        // Error("insufficient balance")
+        let data = "0x08c379a00000000000000000000000000000000000000000000000000000000000000020\
+                    0000000000000000000000000000000000000000000000000000000000000014\
+                    696e73756666696369656e742062616c616e6365000000000000000000000000";
+        let err = YieldFarmingError::from_contract_call(rpc_error_with_data("execution reverted", serde_json::json!(data)));
+        assert!(matches!(err, YieldFarmingError::Reverted { reason: Some(ref r) } if r == "insufficient balance"));
+    }
+}
@@ -0,0 +1,484 @@
+use futures::{Stream, TryStreamExt};
+use std::time::Duration;
+use web3::{
+    signing::keccak256,
+    types::{Address, BlockNumber, FilterBuilder, Log, TransactionReceipt, H256, U256},
+    api::Eth,
+    DuplexTransport, Transport,
+};
+
+use crate::{retry, YieldFarmingClient, YieldFarmingError, DEFAULT_MAX_POLL_ATTEMPTS, DEFAULT_POLL_INTERVAL};
+
+/// Signature of the pool's deposit event
+pub const DEPOSIT_EVENT_SIGNATURE: &str = "Deposit(address,uint256)";
+
+/// Signature of the pool's withdraw event
+pub const WITHDRAW_EVENT_SIGNATURE: &str = "Withdraw(address,uint256)";
+
+/// Signature of the pool's reward payout event
+pub const REWARD_PAID_EVENT_SIGNATURE: &str = "RewardPaid(address,uint256)";
+
+/// Signature of the pool's emergency withdraw event
+pub const EMERGENCY_WITHDRAW_EVENT_SIGNATURE: &str = "EmergencyWithdraw(address,uint256)";
+
+/// `event_topic(DEPOSIT_EVENT_SIGNATURE)`
+pub const DEPOSIT_TOPIC: H256 = H256([
+    0xe1, 0xff, 0xfc, 0xc4, 0x92, 0x3d, 0x04, 0xb5, 0x59, 0xf4, 0xd2, 0x9a, 0x8b, 0xfc, 0x6c, 0xda,
+    0x04, 0xeb, 0x5b, 0x0d, 0x3c, 0x46, 0x07, 0x51, 0xc2, 0x40, 0x2c, 0x5c, 0x5c, 0xc9, 0x10, 0x9c,
+]);
+
+/// `event_topic(WITHDRAW_EVENT_SIGNATURE)`
+pub const WITHDRAW_TOPIC: H256 = H256([
+    0x88, 0x4e, 0xda, 0xd9, 0xce, 0x6f, 0xa2, 0x44, 0x0d, 0x8a, 0x54, 0xcc, 0x12, 0x34, 0x90, 0xeb,
+    0x96, 0xd2, 0x76, 0x84, 0x79, 0xd4, 0x9f, 0xf9, 0xc7, 0x36, 0x61, 0x25, 0xa9, 0x42, 0x43, 0x64,
+]);
+
+/// `event_topic(REWARD_PAID_EVENT_SIGNATURE)`
+pub const REWARD_PAID_TOPIC: H256 = H256([
+    0xe2, 0x40, 0x36, 0x40, 0xba, 0x68, 0xfe, 0xd3, 0xa2, 0xf8, 0x8b, 0x75, 0x57, 0x55, 0x1d, 0x19,
+    0x93, 0xf8, 0x4b, 0x99, 0xbb, 0x10, 0xff, 0x83, 0x3f, 0x0c, 0xf8, 0xdb, 0x0c, 0x5e, 0x04, 0x86,
+]);
+
+/// `event_topic(EMERGENCY_WITHDRAW_EVENT_SIGNATURE)`
+pub const EMERGENCY_WITHDRAW_TOPIC: H256 = H256([
+    0x5f, 0xaf, 0xa9, 0x9d, 0x06, 0x43, 0x51, 0x38, 0x20, 0xbe, 0x26, 0x65, 0x6b, 0x45, 0x13, 0x0b,
+    0x01, 0xe1, 0xc0, 0x30, 0x62, 0xe1, 0x26, 0x6b, 0xf3, 0x6f, 0x88, 0xcb, 0xd3, 0xbd, 0x96, 0x95,
+]);
+
+/// Signature of the claim event some pools emit instead of `RewardPaid`
+pub const CLAIM_REWARDS_EVENT_SIGNATURE: &str = "ClaimRewards(address,uint256)";
+
+/// Signature of the ERC20 transfer event
+pub const TRANSFER_EVENT_SIGNATURE: &str = "Transfer(address,address,uint256)";
+
+/// `event_topic(TRANSFER_EVENT_SIGNATURE)`
+pub const TRANSFER_TOPIC: H256 = H256([
+    0xdd, 0xf2, 0x52, 0xad, 0x1b, 0xe2, 0xc8, 0x9b, 0x69, 0xc2, 0xb0, 0x68, 0xfc, 0x37, 0x8d, 0xaa,
+    0x95, 0x2b, 0xa7, 0xf1, 0x63, 0xc4, 0xa1, 0x16, 0x28, 0xf5, 0x5a, 0x4d, 0xf5, 0x23, 0xb3, 0xef,
+]);
+
+/// Event signatures the client decodes, for pools whose events use a different parameter layout.
+///
+/// Every event must still carry the user as its first indexed topic and the
+/// amount as the first word of its data. Topics are hashed once, on construction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventSignatures {
+    deposit: H256,
+    withdraw: H256,
+    reward_paid: H256,
+    emergency_withdraw: H256,
+}
+
+impl EventSignatures {
+    pub fn new(deposit: &str, withdraw: &str, reward_paid: &str, emergency_withdraw: &str) -> Self {
+        Self {
+            deposit: event_topic(deposit),
+            withdraw: event_topic(withdraw),
+            reward_paid: event_topic(reward_paid),
+            emergency_withdraw: event_topic(emergency_withdraw),
+        }
+    }
+
+    pub fn deposit_topic(&self) -> H256 {
+        self.deposit
+    }
+
+    pub fn withdraw_topic(&self) -> H256 {
+        self.withdraw
+    }
+
+    pub fn reward_paid_topic(&self) -> H256 {
+        self.reward_paid
+    }
+
+    pub fn emergency_withdraw_topic(&self) -> H256 {
+        self.emergency_withdraw
+    }
+}
+
+impl Default for EventSignatures {
+    fn default() -> Self {
+        Self {
+            deposit: DEPOSIT_TOPIC,
+            withdraw: WITHDRAW_TOPIC,
+            reward_paid: REWARD_PAID_TOPIC,
+            emergency_withdraw: EMERGENCY_WITHDRAW_TOPIC,
+        }
+    }
+}
+
+/// Default maximum number of blocks covered by a single `eth_getLogs` request
+pub const DEFAULT_LOG_CHUNK_SIZE: u64 = 2000;
+
+/// A decoded `Event(address indexed user, uint256 amount)` log, the layout of every `FarmEvent`
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserAmountEvent {
+    pub user: Address,
+    pub amount: U256,
+    pub block_number: u64,
+    pub tx_hash: H256,
+    /// The log was removed from the canonical chain by a reorg
+    pub removed: bool,
+}
+
+/// Compute the topic hash for an event signature
+pub fn event_topic(signature: &str) -> H256 {
+    H256::from(keccak256(signature.as_bytes()))
+}
+
+impl TryFrom<Log> for UserAmountEvent {
+    type Error = YieldFarmingError;
+
+    fn try_from(log: Log) -> Result<Self, Self::Error> {
+        let user_topic = log
+            .topics
+            .get(1)
+            .ok_or_else(|| YieldFarmingError::InvalidLog("missing indexed user topic".into()))?;
+        if log.data.0.len() < 32 {
+            return Err(YieldFarmingError::InvalidLog("missing amount in log data".into()));
+        }
+        let block_number = log
+            .block_number
+            .ok_or_else(|| YieldFarmingError::InvalidLog("log is not yet mined".into()))?;
+        let tx_hash = log
+            .transaction_hash
+            .ok_or_else(|| YieldFarmingError::InvalidLog("log has no transaction hash".into()))?;
+
+        Ok(UserAmountEvent {
+            user: Address::from_slice(&user_topic.as_bytes()[12..]),
+            amount: U256::from_big_endian(&log.data.0[..32]),
+            block_number: block_number.as_u64(),
+            tx_hash,
+            removed: log.removed.unwrap_or(false),
+        })
+    }
+}
+
+/// Any event emitted by the pool that the client understands
+#[derive(Debug, Clone, PartialEq)]
+pub enum FarmEvent {
+    Deposit(UserAmountEvent),
+    Withdraw(UserAmountEvent),
+    RewardPaid(UserAmountEvent),
+    EmergencyWithdraw(UserAmountEvent),
+}
+
+/// Decode a pool log using the default event signatures
+pub fn decode_log(log: &Log) -> Result<FarmEvent, YieldFarmingError> {
+    decode_log_with(log, &EventSignatures::default())
+}
+
+/// Decode a pool log by matching its first topic against `signatures`
+pub fn decode_log_with(log: &Log, signatures: &EventSignatures) -> Result<FarmEvent, YieldFarmingError> {
+    let topic = *log
+        .topics
+        .first()
+        .ok_or_else(|| YieldFarmingError::InvalidLog("log has no topics".into()))?;
+    let variant = if topic == signatures.deposit {
+        FarmEvent::Deposit
+    } else if topic == signatures.withdraw {
+        FarmEvent::Withdraw
+    } else if topic == signatures.reward_paid {
+        FarmEvent::RewardPaid
+    } else if topic == signatures.emergency_withdraw {
+        FarmEvent::EmergencyWithdraw
+    } else {
+        return Err(YieldFarmingError::InvalidLog(format!("unknown event topic {:?}", topic)));
+    };
+    UserAmountEvent::try_from(log.clone()).map(variant)
+}
+
+/// Total amount of `token` transferred to `recipient` in a receipt, or `None` if there were no such transfers.
+///
+/// For fee-on-transfer tokens this is what `recipient` actually received, net of the fee.
+pub(crate) fn received_in_receipt(receipt: &TransactionReceipt, token: Address, recipient: Address) -> Result<Option<U256>, YieldFarmingError> {
+    let recipient_topic = H256::from(recipient);
+    receipt
+        .logs
+        .iter()
+        .filter(|log| {
+            log.address == token
+                && log.topics.first() == Some(&TRANSFER_TOPIC)
+                && log.topics.get(2) == Some(&recipient_topic)
+                && log.data.0.len() >= 32
+        })
+        .map(|log| U256::from_big_endian(&log.data.0[..32]))
+        .try_fold(None, |total: Option<U256>, amount| match total {
+            None => Ok(Some(amount)),
+            Some(total) => total.checked_add(amount).map(Some).ok_or(YieldFarmingError::ArithmeticOverflow("received amount")),
+        })
+}
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Fetch `Deposit` events between two blocks (inclusive), ordered by block and log index
+    pub async fn get_deposits_in_range(&self, from: BlockNumber, to: BlockNumber) -> Result<Vec<UserAmountEvent>, YieldFarmingError> {
+        self.get_events_in_range(self.event_signatures.deposit, from, to, self.request_timeout).await
+    }
+
+    /// Like `get_deposits_in_range`, but allowing each `eth_getLogs` request up to `timeout`
+    pub async fn get_deposits_in_range_with_timeout(
+        &self,
+        from: BlockNumber,
+        to: BlockNumber,
+        timeout: Duration,
+    ) -> Result<Vec<UserAmountEvent>, YieldFarmingError> {
+        self.get_events_in_range(self.event_signatures.deposit, from, to, Some(timeout)).await
+    }
+
+    /// Fetch `Withdraw` events between two blocks (inclusive), ordered by block and log index
+    pub async fn get_withdrawals_in_range(&self, from: BlockNumber, to: BlockNumber) -> Result<Vec<UserAmountEvent>, YieldFarmingError> {
+        self.get_events_in_range(self.event_signatures.withdraw, from, to, self.request_timeout).await
+    }
+
+    /// Like `get_withdrawals_in_range`, but allowing each `eth_getLogs` request up to `timeout`
+    pub async fn get_withdrawals_in_range_with_timeout(
+        &self,
+        from: BlockNumber,
+        to: BlockNumber,
+        timeout: Duration,
+    ) -> Result<Vec<UserAmountEvent>, YieldFarmingError> {
+        self.get_events_in_range(self.event_signatures.withdraw, from, to, Some(timeout)).await
+    }
+
+    async fn get_events_in_range<E>(
+        &self,
+        topic: H256,
+        from: BlockNumber,
+        to: BlockNumber,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<E>, YieldFarmingError>
+    where
+        E: TryFrom<Log, Error = YieldFarmingError>,
+    {
+        let logs = self.get_logs_in_range(vec![topic], None, from, to, timeout).await?;
+        logs.into_iter().map(E::try_from).collect()
+    }
+
+    /// Fetch the pool's logs matching any of `topics`, split into windows of at most `log_chunk_size` blocks.
+    ///
+    /// With `confirmed_only` configured, blocks with fewer confirmations are left out of the range.
+    /// With `user`, only logs whose first indexed topic is that address are returned.
+    /// `timeout` bounds each `eth_getLogs` request rather than the whole range.
+    pub(crate) async fn get_logs_in_range(
+        &self,
+        topics: Vec<H256>,
+        user: Option<Address>,
+        from: BlockNumber,
+        to: BlockNumber,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<Log>, YieldFarmingError> {
+        let from = self.resolve_block_number(from).await?;
+        let mut to = self.resolve_block_number(to).await?;
+        if self.log_confirmations > 0 {
+            let latest = retry::with_timeout(self.request_timeout, async { Ok(self.web3.eth().block_number().await?) })
+                .await?
+                .as_u64();
+            to = to.min(latest.saturating_sub(self.log_confirmations));
+        }
+        let chunk_size = self.log_chunk_size.max(1);
+        let user_topic = user.map(|user| vec![H256::from(user)]);
+
+        let mut logs = Vec::new();
+        let mut start = from;
+        while start <= to {
+            let end = start.saturating_add(chunk_size - 1).min(to);
+            let filter = FilterBuilder::default()
+                .address(vec![self.contract.address()])
+                .topics(Some(topics.clone()), user_topic.clone(), None, None)
+                .from_block(start.into())
+                .to_block(end.into())
+                .build();
+            logs.extend(retry::with_timeout(timeout, async { Ok(self.web3.eth().logs(filter).await?) }).await?);
+
+            if end == u64::MAX {
+                break;
+            }
+            start = end + 1;
+        }
+
+        logs.sort_by_key(|log| (log.block_number, log.log_index));
+        Ok(logs)
+    }
+}
+
+impl<T: DuplexTransport> YieldFarmingClient<T> {
+    /// Stream `Deposit` events emitted by the pool.
+    ///
+    /// If the subscription cannot be created or the socket drops, the error is
+    /// yielded as the last item and the caller should resubscribe. With
+    /// `confirmed_only` configured, each event is held back until it has that
+    /// many confirmations; events with `removed` set are passed on immediately.
+    pub fn subscribe_deposits(&self) -> impl Stream<Item = Result<UserAmountEvent, YieldFarmingError>> {
+        self.subscribe_event(self.event_signatures.deposit)
+    }
+
+    /// Stream `Withdraw` events emitted by the pool
+    pub fn subscribe_withdrawals(&self) -> impl Stream<Item = Result<UserAmountEvent, YieldFarmingError>> {
+        self.subscribe_event(self.event_signatures.withdraw)
+    }
+
+    fn subscribe_event<E>(&self, topic: H256) -> impl Stream<Item = Result<E, YieldFarmingError>>
+    where
+        E: TryFrom<Log, Error = YieldFarmingError>,
+    {
+        let filter = FilterBuilder::default()
+            .address(vec![self.contract.address()])
+            .topics(Some(vec![topic]), None, None, None)
+            .build();
+        let eth_subscribe = self.web3.eth_subscribe();
+        let eth = self.web3.eth();
+        let confirmations = self.log_confirmations;
+        let timeout = self.request_timeout;
+
+        futures::stream::once(retry::with_timeout(timeout, async move { Ok(eth_subscribe.subscribe_logs(filter).await?) }))
+            .map_ok(|logs| logs.map_err(YieldFarmingError::from))
+            .try_flatten()
+            .and_then(move |log| {
+                let eth = eth.clone();
+                async move {
+                    if let (Some(block), false) = (log.block_number, log.removed.unwrap_or(false)) {
+                        wait_until_buried(&eth, block.as_u64(), confirmations, timeout).await?;
+                    }
+                    E::try_from(log)
+                }
+            })
+    }
+}
+
+/// Wait until `block` has at least `confirmations` blocks on top of it, polling the chain head.
+///
+/// Fails with `Timeout` if the head hasn't got there after `DEFAULT_MAX_POLL_ATTEMPTS` polls.
+async fn wait_until_buried<T: Transport>(eth: &Eth<T>, block: u64, confirmations: u64, timeout: Option<Duration>) -> Result<(), YieldFarmingError> {
+    if confirmations == 0 {
+        return Ok(());
+    }
+    for _ in 0..DEFAULT_MAX_POLL_ATTEMPTS {
+        let head = retry::with_timeout(timeout, async { Ok(eth.block_number().await?) }).await?;
+        if head.as_u64() >= block.saturating_add(confirmations) {
+            return Ok(());
+        }
+        tokio::time::sleep(DEFAULT_POLL_INTERVAL).await;
+    }
+    Err(YieldFarmingError::Timeout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deposit_topic() {
+        // keccak256("Deposit(address,uint256)")
+        assert_eq!(
+            format!("{:?}", event_topic(DEPOSIT_EVENT_SIGNATURE)),
+            "0xe1fffcc4923d04b559f4d29a8bfc6cda04eb5b0d3c460751c2402c5c5cc9109c"
+        );
+    }
+
+    #[test]
+    fn test_precomputed_topics() {
+        assert_eq!(DEPOSIT_TOPIC, event_topic(DEPOSIT_EVENT_SIGNATURE));
+        assert_eq!(WITHDRAW_TOPIC, event_topic(WITHDRAW_EVENT_SIGNATURE));
+        assert_eq!(REWARD_PAID_TOPIC, event_topic(REWARD_PAID_EVENT_SIGNATURE));
+        assert_eq!(EMERGENCY_WITHDRAW_TOPIC, event_topic(EMERGENCY_WITHDRAW_EVENT_SIGNATURE));
+        assert_eq!(TRANSFER_TOPIC, event_topic(TRANSFER_EVENT_SIGNATURE));
+    }
+
+    #[test]
+    fn test_custom_signatures_match_defaults() {
+        let signatures = EventSignatures::new(
+            DEPOSIT_EVENT_SIGNATURE,
+            WITHDRAW_EVENT_SIGNATURE,
+            REWARD_PAID_EVENT_SIGNATURE,
+            EMERGENCY_WITHDRAW_EVENT_SIGNATURE,
+        );
+        assert_eq!(signatures, EventSignatures::default());
+    }
+
+    #[test]
+    fn test_received_in_receipt() {
+        let token = Address::repeat_byte(0x01);
+        let pool = Address::repeat_byte(0x02);
+        let transfer = |to: Address, amount: U256| {
+            let mut data = [0u8; 32];
+            amount.to_big_endian(&mut data);
+            Log {
+                address: token,
+                topics: vec![TRANSFER_TOPIC, H256::from(Address::repeat_byte(0x03)), H256::from(to)],
+                data: data.to_vec().into(),
+                block_hash: None,
+                block_number: None,
+                transaction_hash: None,
+                transaction_index: None,
+                log_index: None,
+                transaction_log_index: None,
+                log_type: None,
+                removed: None,
+            }
+        };
+        // A 2% transfer tax: 98 to the pool, 2 to the fee collector
+        let receipt = TransactionReceipt {
+            logs: vec![transfer(pool, 98.into()), transfer(Address::repeat_byte(0x04), 2.into())],
+            ..TransactionReceipt::default()
+        };
+
+        assert_eq!(received_in_receipt(&receipt, token, pool).unwrap(), Some(U256::from(98)));
+        assert_eq!(received_in_receipt(&receipt, pool, pool).unwrap(), None);
+
+        let overflowing = TransactionReceipt {
+            logs: vec![transfer(pool, U256::MAX), transfer(pool, 1.into())],
+            ..TransactionReceipt::default()
+        };
+        assert!(matches!(received_in_receipt(&overflowing, token, pool), Err(YieldFarmingError::ArithmeticOverflow(_))));
+    }
+
+    #[test]
+    fn test_decode_deposit_log() {
+        let user = Address::from_low_u64_be(0xabc);
+        let mut data = [0u8; 32];
+        U256::from(1000).to_big_endian(&mut data);
+        let log = Log {
+            address: Address::zero(),
+            topics: vec![event_topic(DEPOSIT_EVENT_SIGNATURE), H256::from(user)],
+            data: data.to_vec().into(),
+            block_hash: None,
+            block_number: Some(42u64.into()),
+            transaction_hash: Some(H256::repeat_byte(1)),
+            transaction_index: None,
+            log_index: None,
+            transaction_log_index: None,
+            log_type: None,
+            removed: None,
+        };
+
+        let event = UserAmountEvent::try_from(log.clone()).unwrap();
+        assert_eq!(event.user, user);
+        assert_eq!(event.amount, U256::from(1000));
+        assert_eq!(event.block_number, 42);
+        assert!(!event.removed);
+        assert_eq!(decode_log(&log).unwrap(), FarmEvent::Deposit(event));
+
+        let removed = Log { removed: Some(true), ..log.clone() };
+        assert!(UserAmountEvent::try_from(removed).unwrap().removed);
+
+        let unknown = Log { topics: vec![H256::zero()], ..log };
+        assert!(matches!(decode_log(&unknown), Err(YieldFarmingError::InvalidLog(_))));
+    }
+
+    #[tokio::test]
+    async fn test_confirmed_only_caps_range() {
+        let transport = crate::testing::MockTransport::new();
+        transport.set_response("eth_blockNumber", serde_json::json!("0x64"));
+        transport.set_response("eth_getLogs", serde_json::json!([]));
+        let mut client = YieldFarmingClient::from_transport(transport.clone(), Address::zero(), b"[]").unwrap();
+        client.log_confirmations = 10;
+
+        client.get_deposits_in_range(BlockNumber::Number(50.into()), BlockNumber::Latest).await.unwrap();
+        let (_, params) = transport.requests().into_iter().find(|(method, _)| method == "eth_getLogs").unwrap();
+        assert_eq!(params[0]["toBlock"], "0x5a");
+
+        // Nothing in the range is buried deep enough yet
+        client.get_deposits_in_range(BlockNumber::Number(95.into()), BlockNumber::Latest).await.unwrap();
+        assert_eq!(transport.requests().iter().filter(|(method, _)| method == "eth_getLogs").count(), 1);
+    }
+}
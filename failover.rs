@@ -0,0 +1,174 @@
+//! Transport that spreads a client over several RPC endpoints, failing over when one goes down.
+
+use futures::future::{BoxFuture, FutureExt};
+use jsonrpc_core as rpc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use web3::{transports::Http, RequestId, Transport};
+
+use crate::{retry, trace, YieldFarmingError};
+
+/// How long requests stay on a fallback endpoint before the primary is tried again
+pub const DEFAULT_FAILOVER_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Methods that submit transactions, which are never resent to another endpoint
+const WRITE_METHODS: [&str; 2] = ["eth_sendTransaction", "eth_sendRawTransaction"];
+
+#[derive(Debug)]
+struct FailoverState {
+    /// Index of the endpoint requests currently go to
+    active: usize,
+    /// When requests last moved off the primary
+    failed_over_at: Option<Instant>,
+}
+
+/// Transport over a prioritized list of endpoints that fails over to the next one on connection errors.
+///
+/// Requests go to the first endpoint until it fails with a transport error or
+/// rate limiting, then to the next, returning to the first once `cooldown` has
+/// passed. JSON-RPC errors such as reverts are answers, not outages, and are
+/// returned as is.
+///
+/// Reads are retried on each remaining endpoint within the same request. Writes
+/// are not: a node that timed out may still have broadcast the transaction, and
+/// `eth_sendTransaction` relies on accounts unlocked on that particular node. A
+/// failed write only moves later requests to the next endpoint; use
+/// `claim_rewards_idempotent` or the in-flight guard to resubmit safely.
+///
+/// Clones share the failover state.
+#[derive(Debug, Clone)]
+pub struct MultiTransport<T: Transport = Http> {
+    endpoints: Arc<Vec<T>>,
+    cooldown: Duration,
+    state: Arc<Mutex<FailoverState>>,
+}
+
+impl<T: Transport> MultiTransport<T> {
+    /// Fail over between `endpoints`, highest priority first
+    pub fn new(endpoints: Vec<T>) -> Result<Self, YieldFarmingError> {
+        if endpoints.is_empty() {
+            return Err(YieldFarmingError::MissingField("rpc_urls"));
+        }
+        Ok(Self {
+            endpoints: Arc::new(endpoints),
+            cooldown: DEFAULT_FAILOVER_COOLDOWN,
+            state: Arc::new(Mutex::new(FailoverState { active: 0, failed_over_at: None })),
+        })
+    }
+
+    /// Return to the primary endpoint `cooldown` after failing over instead of `DEFAULT_FAILOVER_COOLDOWN`
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Index of the endpoint requests currently go to
+    pub fn active_endpoint(&self) -> usize {
+        self.lock().active
+    }
+
+    /// The endpoint to start the next request at, moving back to the primary once the cooldown is over
+    fn start(&self) -> usize {
+        let mut state = self.lock();
+        if state.failed_over_at.is_some_and(|at| at.elapsed() >= self.cooldown) {
+            *state = FailoverState { active: 0, failed_over_at: None };
+        }
+        state.active
+    }
+
+    /// Move off `index` if requests are still going to it
+    fn record_failure(&self, index: usize) {
+        let mut state = self.lock();
+        if state.active == index {
+            state.active = (index + 1) % self.endpoints.len();
+            state.failed_over_at = Some(Instant::now());
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, FailoverState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<T> Transport for MultiTransport<T>
+where
+    T: Transport + Send + Sync + 'static,
+    T::Out: Send + 'static,
+{
+    type Out = BoxFuture<'static, web3::Result<rpc::Value>>;
+
+    fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+        self.endpoints[0].prepare(method, params)
+    }
+
+    fn send(&self, id: RequestId, request: rpc::Call) -> Self::Out {
+        let is_write = matches!(&request, rpc::Call::MethodCall(call) if WRITE_METHODS.contains(&call.method.as_str()));
+        let this = self.clone();
+
+        async move {
+            let start = this.start();
+            let attempts = if is_write { 1 } else { this.endpoints.len() };
+            let mut last_err = None;
+            for offset in 0..attempts {
+                let index = (start + offset) % this.endpoints.len();
+                match this.endpoints[index].send(id, request.clone()).await {
+                    Err(err) if retry::is_transient_web3(&err) => {
+                        trace::warning!(endpoint = index, error = %err, "RPC endpoint failed; failing over");
+                        this.record_failure(index);
+                        last_err = Some(err);
+                    }
+                    result => return result,
+                }
+            }
+            Err(last_err.unwrap_or(web3::Error::Unreachable))
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTransport;
+    use futures::future;
+
+    /// A mock endpoint, or one that is unreachable for every request
+    #[derive(Debug, Clone)]
+    enum Endpoint {
+        Down,
+        Up(MockTransport),
+    }
+
+    impl Transport for Endpoint {
+        type Out = BoxFuture<'static, web3::Result<rpc::Value>>;
+
+        fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+            (1, web3::helpers::build_request(1, method, params))
+        }
+
+        fn send(&self, id: RequestId, request: rpc::Call) -> Self::Out {
+            match self {
+                Endpoint::Down => future::ready(Err(web3::Error::Unreachable)).boxed(),
+                Endpoint::Up(transport) => transport.send(id, request),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reads_fail_over_and_writes_do_not() {
+        let backup = MockTransport::new();
+        backup.set_response("eth_blockNumber", serde_json::json!("0x10"));
+        let transport = MultiTransport::new(vec![Endpoint::Down, Endpoint::Up(backup)])
+            .unwrap()
+            .with_cooldown(Duration::ZERO);
+
+        let write = transport.execute("eth_sendRawTransaction", vec![serde_json::json!("0x00")]).await;
+        assert!(matches!(write, Err(web3::Error::Unreachable)));
+        assert_eq!(transport.active_endpoint(), 1);
+
+        // The cooldown has passed, so the read starts back at the primary and fails over
+        let block = transport.execute("eth_blockNumber", vec![]).await.unwrap();
+        assert_eq!(block, serde_json::json!("0x10"));
+        assert_eq!(transport.active_endpoint(), 1);
+    }
+}
@@ -0,0 +1,104 @@
+//! 18-decimal fixed-point arithmetic for reward and APY math.
+//!
+//! Values are `U256`s scaled by `PRECISION`, so `PRECISION` itself is `1.0`.
+//! Intermediate products are computed in 512 bits, and conversion to `f64`
+//! happens only at the display boundary via `to_f64`, so computed yields don't
+//! pick up float rounding from 18-decimal token amounts.
+
+use web3::{ethabi::ethereum_types::U512, types::U256};
+
+use crate::units;
+
+/// Scaling factor of fixed-point values, `1e18`
+pub const PRECISION: U256 = U256([1_000_000_000_000_000_000, 0, 0, 0]);
+
+/// Decimals of fixed-point values
+const PRECISION_DECIMALS: u8 = 18;
+
+/// Convert an amount in base units of a token with `decimals` into fixed point
+pub fn from_base_units(amount: U256, decimals: u8) -> U256 {
+    match decimals.cmp(&PRECISION_DECIMALS) {
+        std::cmp::Ordering::Equal => amount,
+        std::cmp::Ordering::Less => amount.saturating_mul(U256::exp10((PRECISION_DECIMALS - decimals) as usize)),
+        std::cmp::Ordering::Greater => amount / U256::exp10((decimals - PRECISION_DECIMALS) as usize),
+    }
+}
+
+/// Convert a float such as an oracle price into fixed point, truncating beyond 18 decimals
+pub fn from_f64(value: f64) -> U256 {
+    units::to_wei(value, PRECISION_DECIMALS)
+}
+
+/// Convert a fixed-point value into a float for display
+pub fn to_f64(value: U256) -> f64 {
+    units::from_wei(value, PRECISION_DECIMALS)
+}
+
+/// Fixed-point product `a * b`, saturating at `U256::MAX`
+pub fn mul(a: U256, b: U256) -> U256 {
+    mul_div(a, b, PRECISION)
+}
+
+/// Fixed-point quotient `a / b`, or zero when `b` is zero
+pub fn div(a: U256, b: U256) -> U256 {
+    mul_div(a, PRECISION, b)
+}
+
+/// `a * b / denominator` without overflow in the product, saturating at `U256::MAX`.
+///
+/// Works on plain integers as well as fixed-point values; zero when `denominator` is zero.
+pub fn mul_div(a: U256, b: U256, denominator: U256) -> U256 {
+    if denominator.is_zero() {
+        return U256::zero();
+    }
+    narrow(a.full_mul(b) / U512::from(denominator))
+}
+
+/// `a * b / denominator`, or `None` if the result doesn't fit in a `U256`.
+///
+/// Zero when `denominator` is zero, like `mul_div`.
+pub fn checked_mul_div(a: U256, b: U256, denominator: U256) -> Option<U256> {
+    if denominator.is_zero() {
+        return Some(U256::zero());
+    }
+    U256::try_from(a.full_mul(b) / U512::from(denominator)).ok()
+}
+
+/// `part` as a fixed-point percentage of `whole`, or zero when `whole` is zero
+pub fn percentage(part: U256, whole: U256) -> U256 {
+    div(part, whole).saturating_mul(100.into())
+}
+
+fn narrow(value: U512) -> U256 {
+    U256::try_from(value).unwrap_or(U256::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scaling_between_decimals() {
+        assert_eq!(from_base_units(1_500_000.into(), 6), U256::from(1_500_000_000_000_000_000u64));
+        assert_eq!(from_base_units(PRECISION, 18), PRECISION);
+        assert_eq!(from_base_units(U256::exp10(24), 24), PRECISION);
+    }
+
+    #[test]
+    fn test_mul_and_div_keep_wei_precision() {
+        // 1 wei short of 1e27 tokens loses that wei in f64, but not here
+        let amount = U256::exp10(45) - 1;
+        assert_eq!(mul(amount, PRECISION), amount);
+        assert_eq!(div(amount, PRECISION), amount);
+        assert_eq!(mul(U256::MAX, U256::MAX), U256::MAX);
+        assert_eq!(div(PRECISION, U256::zero()), U256::zero());
+        assert_eq!(checked_mul_div(U256::MAX, 2.into(), 2.into()), Some(U256::MAX));
+        assert_eq!(checked_mul_div(U256::MAX, 3.into(), 2.into()), None);
+    }
+
+    #[test]
+    fn test_percentage() {
+        assert_eq!(percentage(from_f64(1.0), from_f64(8.0)), from_f64(12.5));
+        assert_eq!(to_f64(from_f64(0.1)), 0.1);
+    }
+}
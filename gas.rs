@@ -0,0 +1,238 @@
+use std::time::Instant;
+use web3::{
+    contract::{tokens::Tokenize, Options},
+    types::{Address, BlockNumber, FeeHistory, U256},
+    Transport,
+};
+
+use crate::{fixed, retry, YieldFarmingClient, YieldFarmingError};
+
+/// Default headroom over the node's gas estimate when no gas limit is set
+pub const DEFAULT_GAS_LIMIT_MULTIPLIER: f64 = 1.2;
+
+/// Number of recent blocks sampled by `suggest_fees`
+const FEE_HISTORY_BLOCKS: u64 = 20;
+
+/// Gas and submission settings applied to state-changing contract calls
+#[derive(Debug, Clone, PartialEq)]
+pub struct GasConfig {
+    /// Maximum total fee per gas (EIP-1559)
+    pub max_fee_per_gas: Option<U256>,
+    /// Maximum priority fee (tip) per gas (EIP-1559)
+    pub max_priority_fee_per_gas: Option<U256>,
+    /// Fixed gas limit, estimated and scaled by `gas_limit_multiplier` when unset
+    pub gas_limit: Option<U256>,
+    /// Factor applied to the gas estimate when `gas_limit` is unset, so state changing between
+    /// estimation and inclusion doesn't run the transaction out of gas. Values below 1 count as 1.
+    pub gas_limit_multiplier: f64,
+    /// Explicit transaction nonce, picked by the node when unset
+    pub nonce: Option<U256>,
+    /// Query the pool's `paused()` view first and fail fast instead of sending a doomed transaction
+    pub check_paused: bool,
+    /// Deadline for the transaction to be mined. When set, the `*_with_gas` methods wait for it to be
+    /// mined and, once the deadline passes, send a cancellation and fail with `Expired`.
+    pub valid_until: Option<Instant>,
+}
+
+impl Default for GasConfig {
+    fn default() -> Self {
+        Self {
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            gas_limit: None,
+            gas_limit_multiplier: DEFAULT_GAS_LIMIT_MULTIPLIER,
+            nonce: None,
+            check_paused: false,
+            valid_until: None,
+        }
+    }
+}
+
+impl GasConfig {
+    /// `estimate` scaled by `gas_limit_multiplier`, saturating at `U256::MAX`
+    pub fn buffered_gas_limit(&self, estimate: U256) -> U256 {
+        // Per-mille precision is plenty for a safety margin and keeps the math in integers
+        let per_mille = (self.gas_limit_multiplier.max(1.0) * 1000.0).round() as u64;
+        fixed::mul_div(estimate, per_mille.into(), 1000.into())
+    }
+
+    /// Build call options from this configuration.
+    ///
+    /// The gas limit stays unset unless `gas_limit` is; `YieldFarmingClient`'s `*_with_gas`
+    /// methods fill it in from a buffered estimate.
+    pub fn to_options(&self) -> Options {
+        let mut options = Options {
+            gas: self.gas_limit,
+            nonce: self.nonce,
+            max_fee_per_gas: self.max_fee_per_gas,
+            max_priority_fee_per_gas: self.max_priority_fee_per_gas,
+            ..Options::default()
+        };
+
+        // Dynamic fee transaction; the legacy gas price stays unset
+        if self.max_fee_per_gas.is_some() && self.max_priority_fee_per_gas.is_some() {
+            options.transaction_type = Some(2u64.into());
+            options.gas_price = None;
+        }
+
+        options
+    }
+}
+
+/// How quickly a transaction should be included, selecting the priority fee percentile paid by recent blocks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FeeSpeed {
+    Slow,
+    #[default]
+    Standard,
+    Fast,
+}
+
+impl FeeSpeed {
+    fn reward_percentile(self) -> f64 {
+        match self {
+            FeeSpeed::Slow => 10.0,
+            FeeSpeed::Standard => 50.0,
+            FeeSpeed::Fast => 90.0,
+        }
+    }
+}
+
+/// EIP-1559 fees suggested from recent fee history
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeSuggestion {
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+}
+
+impl FeeSuggestion {
+    /// Gas settings using these fees and otherwise defaults
+    pub fn to_gas_config(&self) -> GasConfig {
+        GasConfig {
+            max_fee_per_gas: Some(self.max_fee_per_gas),
+            max_priority_fee_per_gas: Some(self.max_priority_fee_per_gas),
+            ..GasConfig::default()
+        }
+    }
+}
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Call options for `func` under `gas`, with a buffered estimate as the gas limit if none is set
+    pub(crate) async fn options_with_gas_limit(
+        &self,
+        func: &str,
+        params: impl Tokenize,
+        account: Address,
+        gas: &GasConfig,
+    ) -> Result<Options, YieldFarmingError> {
+        let mut options = gas.to_options();
+        // Without headroom the node's own estimate at send time is just as good
+        if options.gas.is_none() && gas.gas_limit_multiplier > 1.0 {
+            let estimate = retry::with_timeout(self.request_timeout, async {
+                self.contract
+                    .estimate_gas(func, params, account, Options::default())
+                    .await
+                    .map_err(|err| self.contract_error(err))
+            })
+            .await?;
+            options.gas = Some(gas.buffered_gas_limit(estimate));
+        }
+        Ok(options)
+    }
+
+    /// Suggest EIP-1559 fees from `eth_feeHistory` over recent blocks.
+    ///
+    /// The priority fee is the median of the `speed` percentile tip paid in each
+    /// non-empty block. The max fee leaves room for the base fee to double, or
+    /// for its recent peak if that is higher, so the transaction stays
+    /// includable through several full blocks.
+    pub async fn suggest_fees(&self, speed: FeeSpeed) -> Result<FeeSuggestion, YieldFarmingError> {
+        let history = self.retry_policy
+            .retry(|| retry::with_timeout(self.request_timeout, async {
+                let history = self.web3.eth()
+                    .fee_history(FEE_HISTORY_BLOCKS.into(), BlockNumber::Latest, Some(vec![speed.reward_percentile()]))
+                    .await?;
+                Ok(history)
+            }))
+            .await?;
+
+        Ok(suggest_from_history(&history))
+    }
+}
+
+fn suggest_from_history(history: &FeeHistory) -> FeeSuggestion {
+    // The last entry is the base fee of the next, not yet mined, block
+    let next_base_fee = history.base_fee_per_gas.last().copied().unwrap_or_default();
+    let peak_base_fee = history.base_fee_per_gas.iter().copied().max().unwrap_or_default();
+
+    let mut tips: Vec<U256> = history
+        .reward
+        .iter()
+        .flatten()
+        .filter_map(|rewards| rewards.first().copied())
+        .filter(|tip| !tip.is_zero())
+        .collect();
+    tips.sort();
+    let priority_fee = tips.get(tips.len() / 2).copied().unwrap_or_default();
+
+    let base_fee_budget = next_base_fee.saturating_mul(2.into()).max(peak_base_fee);
+    FeeSuggestion {
+        max_fee_per_gas: base_fee_budget.saturating_add(priority_fee),
+        max_priority_fee_per_gas: priority_fee,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eip1559_options() {
+        let config = GasConfig {
+            max_fee_per_gas: Some(U256::from(50_000_000_000u64)),
+            max_priority_fee_per_gas: Some(U256::from(2_000_000_000u64)),
+            gas_limit: Some(U256::from(300_000)),
+            ..GasConfig::default()
+        };
+
+        let options = config.to_options();
+        assert_eq!(options.transaction_type, Some(2u64.into()));
+        assert_eq!(options.gas_price, None);
+        assert_eq!(options.gas, Some(U256::from(300_000)));
+        assert_eq!(options.max_priority_fee_per_gas, Some(U256::from(2_000_000_000u64)));
+    }
+
+    #[test]
+    fn test_suggest_from_history() {
+        let gwei = |n: u64| U256::from(n * 1_000_000_000);
+        let history = FeeHistory {
+            oldest_block: BlockNumber::Number(100.into()),
+            base_fee_per_gas: vec![gwei(10), gwei(12), gwei(11)],
+            gas_used_ratio: vec![0.5, 0.4],
+            // The empty block's zero tip is ignored
+            reward: Some(vec![vec![gwei(2)], vec![U256::zero()], vec![gwei(3)]]),
+        };
+
+        let suggestion = suggest_from_history(&history);
+        assert_eq!(suggestion.max_priority_fee_per_gas, gwei(3));
+        assert_eq!(suggestion.max_fee_per_gas, gwei(22 + 3));
+        assert_eq!(suggestion.to_gas_config().to_options().transaction_type, Some(2u64.into()));
+    }
+
+    #[test]
+    fn test_buffered_gas_limit() {
+        let config = GasConfig::default();
+        assert_eq!(config.buffered_gas_limit(100_000.into()), U256::from(120_000));
+        assert_eq!(config.buffered_gas_limit(U256::MAX), U256::MAX);
+
+        let below_one = GasConfig { gas_limit_multiplier: 0.5, ..GasConfig::default() };
+        assert_eq!(below_one.buffered_gas_limit(100_000.into()), U256::from(100_000));
+        let nan = GasConfig { gas_limit_multiplier: f64::NAN, ..GasConfig::default() };
+        assert_eq!(nan.buffered_gas_limit(100_000.into()), U256::from(100_000));
+    }
+
+    #[test]
+    fn test_default_is_plain_options() {
+        assert_eq!(GasConfig::default().to_options(), Options::default());
+    }
+}
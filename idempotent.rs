@@ -0,0 +1,91 @@
+use web3::{
+    types::{Address, BlockNumber, H256},
+    Transport,
+};
+
+use crate::{retry, trace, YieldFarmingClient, YieldFarmingError, DEFAULT_MAX_POLL_ATTEMPTS, DEFAULT_POLL_INTERVAL};
+
+/// What `claim_rewards_idempotent` did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimOutcome {
+    /// A new claim transaction was sent
+    Sent(H256),
+    /// An earlier claim from the account landed, so nothing new was sent
+    PriorConfirmed,
+}
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Claim `account`'s rewards without double-submitting under a flaky connection.
+    ///
+    /// Transactions the account already has in the mempool are waited out first,
+    /// since one may be a claim whose response was lost. If a claim send itself
+    /// fails with a transient error, the node may still have accepted it, so the
+    /// account's mempool is waited out again before retrying once. Either way a
+    /// drop in pending rewards means a prior claim landed and nothing is resent.
+    pub async fn claim_rewards_idempotent(&self, account: Address) -> Result<ClaimOutcome, YieldFarmingError> {
+        let before = self.get_pending_rewards(account).await?;
+        if self.settle_pending(account).await? && self.get_pending_rewards(account).await? < before {
+            return Ok(ClaimOutcome::PriorConfirmed);
+        }
+
+        match self.claim_rewards(account).await {
+            Ok(tx_hash) => Ok(ClaimOutcome::Sent(tx_hash)),
+            Err(err) if retry::is_transient(&err) => {
+                trace::warning!(?account, error = %err, "claim send failed; checking whether it landed before retrying");
+                self.settle_pending(account).await?;
+                if self.get_pending_rewards(account).await? < before {
+                    return Ok(ClaimOutcome::PriorConfirmed);
+                }
+                Ok(ClaimOutcome::Sent(self.claim_rewards(account).await?))
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Wait until every transaction `account` has in the mempool is mined, returning whether it had any
+    async fn settle_pending(&self, account: Address) -> Result<bool, YieldFarmingError> {
+        let eth = &self.web3.eth();
+        let count = |block| retry::with_timeout(self.request_timeout, async move { Ok(eth.transaction_count(account, Some(block)).await?) });
+        let pending = count(BlockNumber::Pending).await?;
+        if count(BlockNumber::Latest).await? >= pending {
+            return Ok(false);
+        }
+
+        for _ in 0..DEFAULT_MAX_POLL_ATTEMPTS {
+            tokio::time::sleep(DEFAULT_POLL_INTERVAL).await;
+            if count(BlockNumber::Latest).await? >= pending {
+                return Ok(true);
+            }
+        }
+
+        Err(YieldFarmingError::Timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTransport;
+    use web3::ethabi::Token;
+
+    const POOL_ABI: &[u8] = br#"[
+        {"type":"function","name":"pendingRewards","stateMutability":"view","inputs":[{"name":"user","type":"address"}],"outputs":[{"name":"","type":"uint256"}]},
+        {"type":"function","name":"claimRewards","stateMutability":"nonpayable","inputs":[],"outputs":[]}
+    ]"#;
+
+    #[tokio::test]
+    async fn test_claim_idempotent_detects_prior_claim() {
+        let transport = MockTransport::new();
+        transport.push_call_result(&[Token::Uint(10.into())]);
+        transport.push_call_result(&[Token::Uint(0.into())]);
+        // Pending count, then the mined count before and after the prior claim lands
+        transport.push_response("eth_getTransactionCount", serde_json::json!("0x6"));
+        transport.push_response("eth_getTransactionCount", serde_json::json!("0x5"));
+        transport.set_response("eth_getTransactionCount", serde_json::json!("0x6"));
+
+        let client = YieldFarmingClient::from_transport(transport.clone(), Address::zero(), POOL_ABI).unwrap();
+        let outcome = client.claim_rewards_idempotent(Address::repeat_byte(0x01)).await.unwrap();
+        assert_eq!(outcome, ClaimOutcome::PriorConfirmed);
+        assert!(transport.requests().iter().all(|(method, _)| method != "eth_sendTransaction"));
+    }
+}
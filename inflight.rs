@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use web3::{
+    api::Eth,
+    ethabi::{self, Token},
+    types::{Address, TransactionId, H256},
+    Transport,
+};
+
+use crate::{retry, trace, YieldFarmingError};
+
+/// Contract function, sender and ABI-encoded arguments of a submitted call
+type InFlightKey = (String, Address, Vec<u8>);
+
+/// Hash last submitted for one key, locked across submission so concurrent identical calls can't both send
+type Slot = Arc<Mutex<Option<H256>>>;
+
+/// Remembers unmined transactions so an identical call, e.g. from a double-clicked
+/// button, returns the existing hash instead of submitting a duplicate
+#[derive(Debug, Default)]
+pub(crate) struct InFlightGuard {
+    enabled: bool,
+    /// Only held briefly to look up a slot, so unrelated calls never wait on each other
+    pending: std::sync::Mutex<HashMap<InFlightKey, Slot>>,
+}
+
+impl InFlightGuard {
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            pending: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn slots(&self) -> std::sync::MutexGuard<'_, HashMap<InFlightKey, Slot>> {
+        self.pending.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Return the hash of an identical unmined call from `account`, or run `send` and remember its hash.
+    ///
+    /// Entries expire once their transaction is mined or the node no longer knows it.
+    pub(crate) async fn submit_once<T, Fut>(
+        &self,
+        eth: &Eth<T>,
+        timeout: Option<Duration>,
+        func: &str,
+        account: Address,
+        params: &[Token],
+        send: impl FnOnce() -> Fut,
+    ) -> Result<H256, YieldFarmingError>
+    where
+        T: Transport,
+        Fut: Future<Output = Result<H256, YieldFarmingError>>,
+    {
+        if !self.enabled {
+            return send().await;
+        }
+
+        self.prune(eth, timeout).await;
+
+        let key = (func.to_string(), account, ethabi::encode(params));
+        let slot = Arc::clone(self.slots().entry(key).or_default());
+        let mut submitted = slot.lock().await;
+        if let Some(tx_hash) = *submitted {
+            if is_unmined(eth, tx_hash, timeout).await? {
+                trace::info!(func, ?tx_hash, "identical transaction already pending");
+                return Ok(tx_hash);
+            }
+            *submitted = None;
+        }
+
+        let tx_hash = send().await?;
+        *submitted = Some(tx_hash);
+        Ok(tx_hash)
+    }
+
+    /// Forget entries whose transaction is mined or unknown to the node, and slots left empty by a failed send.
+    ///
+    /// Slots another call currently holds are skipped, as are ones whose status can't be read.
+    async fn prune<T: Transport>(&self, eth: &Eth<T>, timeout: Option<Duration>) {
+        let slots: Vec<(InFlightKey, Slot)> = self.slots().iter().map(|(key, slot)| (key.clone(), Arc::clone(slot))).collect();
+        let expired = futures::future::join_all(slots.into_iter().map(|(key, slot)| async move {
+            let tx_hash = *slot.try_lock().ok()?;
+            match tx_hash {
+                Some(tx_hash) => matches!(is_unmined(eth, tx_hash, timeout).await, Ok(false)).then_some((key, slot)),
+                None => Some((key, slot)),
+            }
+        }))
+        .await;
+
+        let mut slots = self.slots();
+        for (key, slot) in expired.into_iter().flatten() {
+            // Only the map and this sweep hold the slot, so no call is about to reuse it
+            if slots.get(&key).is_some_and(|current| Arc::ptr_eq(current, &slot) && Arc::strong_count(&slot) == 2) {
+                slots.remove(&key);
+            }
+        }
+    }
+}
+
+/// Whether the node still holds `tx_hash` without having mined it
+async fn is_unmined<T: Transport>(eth: &Eth<T>, tx_hash: H256, timeout: Option<Duration>) -> Result<bool, YieldFarmingError> {
+    let receipt = retry::with_timeout(timeout, async { Ok(eth.transaction_receipt(tx_hash).await?) }).await?;
+    if receipt.is_some_and(|receipt| receipt.block_number.is_some()) {
+        return Ok(false);
+    }
+    let transaction = retry::with_timeout(timeout, async { Ok(eth.transaction(TransactionId::Hash(tx_hash)).await?) }).await?;
+    Ok(transaction.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTransport;
+    use crate::YieldFarmingClient;
+    use web3::types::Transaction;
+
+    const POOL_ABI: &[u8] = br#"[
+        {"type":"function","name":"deposit","stateMutability":"nonpayable","inputs":[{"name":"amount","type":"uint256"}],"outputs":[]}
+    ]"#;
+
+    #[tokio::test]
+    async fn test_identical_pending_deposit_is_not_resubmitted() {
+        let transport = MockTransport::new();
+        transport.set_response("eth_estimateGas", serde_json::json!("0x5208"));
+        transport.set_response("eth_sendTransaction", serde_json::to_value(H256::repeat_byte(0xaa)).unwrap());
+        transport.set_response("eth_getTransactionReceipt", serde_json::Value::Null);
+        transport.set_response("eth_getTransactionByHash", serde_json::to_value(Transaction::default()).unwrap());
+
+        let mut client = YieldFarmingClient::from_transport(transport.clone(), Address::zero(), POOL_ABI).unwrap();
+        client.in_flight = std::sync::Arc::new(InFlightGuard::new(true));
+        let account = Address::repeat_byte(0x01);
+
+        let first = client.deposit(100.into(), account).await.unwrap();
+        let second = client.deposit(100.into(), account).await.unwrap();
+        client.deposit(200.into(), account).await.unwrap();
+
+        assert_eq!(first, second);
+        let sends = transport.requests().into_iter().filter(|(method, _)| method == "eth_sendTransaction").count();
+        assert_eq!(sends, 2);
+    }
+
+    #[tokio::test]
+    async fn test_mined_entries_are_pruned() {
+        let transport = MockTransport::new();
+        transport.set_response("eth_estimateGas", serde_json::json!("0x5208"));
+        transport.set_response("eth_sendTransaction", serde_json::to_value(H256::repeat_byte(0xaa)).unwrap());
+        transport.set_response("eth_getTransactionReceipt", serde_json::Value::Null);
+        transport.set_response("eth_getTransactionByHash", serde_json::to_value(Transaction::default()).unwrap());
+
+        let mut client = YieldFarmingClient::from_transport(transport.clone(), Address::zero(), POOL_ABI).unwrap();
+        let guard = std::sync::Arc::new(InFlightGuard::new(true));
+        client.in_flight = guard.clone();
+        let account = Address::repeat_byte(0x01);
+
+        client.deposit(100.into(), account).await.unwrap();
+        client.deposit(200.into(), account).await.unwrap();
+        assert_eq!(guard.slots().len(), 2);
+
+        let mined = web3::types::TransactionReceipt { block_number: Some(1.into()), ..Default::default() };
+        transport.set_response("eth_getTransactionReceipt", serde_json::to_value(mined).unwrap());
+        client.deposit(300.into(), account).await.unwrap();
+        assert_eq!(guard.slots().len(), 1);
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+use web3::{
+    types::{Address, BlockNumber, U256},
+    Transport,
+};
+
+use crate::events::{self, FarmEvent};
+use crate::{YieldFarmingClient, YieldFarmingError};
+
+/// Accounts per Multicall3 batch when verifying leaderboard balances
+const BALANCE_BATCH_SIZE: usize = 500;
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Rank the accounts that deposited or withdrew between `from` and `to` by current staked balance, largest first.
+    ///
+    /// Net stake per account is rebuilt from the `Deposit`, `Withdraw` and
+    /// `EmergencyWithdraw` logs in the range to find candidates, whose current
+    /// balances are then read with Multicall3 `balanceOf`. Accounts whose
+    /// `balanceOf` reverts are ranked by their event-derived net stake, which
+    /// misses anything deposited before `from`. Accounts with nothing staked are
+    /// left out.
+    ///
+    /// The log scan costs one `eth_getLogs` per `log_chunk_size` blocks and every
+    /// candidate costs a slot in a multicall of up to 500 accounts, so ranges
+    /// covering a pool's whole history can take many requests.
+    pub async fn top_stakers(&self, from: BlockNumber, to: BlockNumber, limit: usize) -> Result<Vec<(Address, U256)>, YieldFarmingError> {
+        let signatures = &self.event_signatures;
+        let topics = vec![signatures.deposit_topic(), signatures.withdraw_topic(), signatures.emergency_withdraw_topic()];
+        let logs = self.get_logs_in_range(topics, None, from, to, self.request_timeout).await?;
+
+        let mut net_stakes = HashMap::new();
+        for log in &logs {
+            apply_event(&mut net_stakes, events::decode_log_with(log, signatures)?);
+        }
+        let accounts: Vec<Address> = net_stakes.keys().copied().collect();
+
+        let mut stakers = Vec::with_capacity(accounts.len());
+        for batch in accounts.chunks(BALANCE_BATCH_SIZE) {
+            let balances = self.get_staked_balances(batch).await?;
+            for (account, balance) in batch.iter().zip(balances) {
+                let balance = balance.unwrap_or_else(|_| net_stakes[account]);
+                if !balance.is_zero() {
+                    stakers.push((*account, balance));
+                }
+            }
+        }
+
+        stakers.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        stakers.truncate(limit);
+        Ok(stakers)
+    }
+}
+
+/// Add a deposit to or take a withdrawal from its account's net stake, floored at zero
+fn apply_event(net_stakes: &mut HashMap<Address, U256>, event: FarmEvent) {
+    let (user, amount, removed, deposit) = match event {
+        FarmEvent::Deposit(event) => (event.user, event.amount, event.removed, true),
+        FarmEvent::Withdraw(event) => (event.user, event.amount, event.removed, false),
+        FarmEvent::EmergencyWithdraw(event) => (event.user, event.amount, event.removed, false),
+        FarmEvent::RewardPaid(_) => return,
+    };
+    if removed {
+        return;
+    }
+    let stake = net_stakes.entry(user).or_default();
+    *stake = if deposit { stake.saturating_add(amount) } else { stake.saturating_sub(amount) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTransport;
+    use web3::{
+        ethabi::Token,
+        types::{Bytes, H256},
+    };
+
+    const POOL_ABI: &[u8] = br#"[
+        {"type":"function","name":"balanceOf","stateMutability":"view","inputs":[{"name":"account","type":"address"}],"outputs":[{"name":"","type":"uint256"}]}
+    ]"#;
+
+    #[tokio::test]
+    async fn test_top_stakers_ranks_by_current_balance() {
+        let transport = MockTransport::new();
+        let log = |signature: &str, user: Address, amount: u64| {
+            let mut data = [0u8; 32];
+            U256::from(amount).to_big_endian(&mut data);
+            serde_json::json!({
+                "address": Address::zero(),
+                "topics": [events::event_topic(signature), H256::from(user)],
+                "data": Bytes(data.to_vec()),
+                "blockNumber": "0x1",
+                "transactionHash": H256::repeat_byte(0x02),
+                "logIndex": "0x0",
+            })
+        };
+        let (alice, bob) = (Address::repeat_byte(0x01), Address::repeat_byte(0x02));
+        transport.set_response("eth_blockNumber", serde_json::json!("0x10"));
+        transport.set_response(
+            "eth_getLogs",
+            serde_json::json!([
+                log(events::DEPOSIT_EVENT_SIGNATURE, alice, 100),
+                log(events::DEPOSIT_EVENT_SIGNATURE, bob, 50),
+                log(events::WITHDRAW_EVENT_SIGNATURE, bob, 50),
+            ]),
+        );
+        let mut balance = [0u8; 32];
+        U256::from(70).to_big_endian(&mut balance);
+        // Every account's balanceOf answers 70; bob's net stake of zero is only the fallback
+        let result = Token::Tuple(vec![Token::Bool(true), Token::Bytes(balance.to_vec())]);
+        transport.push_call_result(&[Token::Array(vec![result.clone(), result])]);
+
+        let client = YieldFarmingClient::from_transport(transport, Address::zero(), POOL_ABI).unwrap();
+        let top = client.top_stakers(BlockNumber::Earliest, BlockNumber::Latest, 1).await.unwrap();
+        assert_eq!(top, vec![(alice, U256::from(70))]);
+    }
+
+    #[test]
+    fn test_apply_event_floors_at_zero() {
+        let mut net_stakes = HashMap::new();
+        let withdraw = events::UserAmountEvent {
+            user: Address::zero(),
+            amount: 5.into(),
+            block_number: 1,
+            tx_hash: H256::zero(),
+            removed: false,
+        };
+        apply_event(&mut net_stakes, FarmEvent::Withdraw(withdraw));
+        assert_eq!(net_stakes[&Address::zero()], U256::zero());
+    }
+}
@@ -0,0 +1,130 @@
+use web3::{types::U256, Transport};
+
+use crate::{YieldFarmingClient, YieldFarmingError};
+
+/// Bounds on the amount accepted by a single deposit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DepositLimits {
+    pub min: Option<U256>,
+    pub max: Option<U256>,
+}
+
+impl DepositLimits {
+    /// Reject `amount` if it falls outside the limits
+    pub fn check(&self, amount: U256) -> Result<(), YieldFarmingError> {
+        if let Some(min) = self.min.filter(|min| amount < *min) {
+            return Err(YieldFarmingError::AmountBelowMinimum { amount, min });
+        }
+        if let Some(max) = self.max.filter(|max| amount > *max) {
+            return Err(YieldFarmingError::AmountAboveMaximum { amount, max });
+        }
+        Ok(())
+    }
+}
+
+/// How much more the pool accepts before its total stake cap is reached
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityInfo {
+    /// Cap on the pool's total stake, `None` if it is uncapped
+    pub max_capacity: Option<U256>,
+    pub current_tvl: U256,
+    /// `max_capacity` minus `current_tvl`, or `None` if the pool is uncapped
+    pub remaining_capacity: Option<U256>,
+}
+
+impl CapacityInfo {
+    fn new(max_capacity: Option<U256>, current_tvl: U256) -> Self {
+        Self {
+            max_capacity,
+            current_tvl,
+            remaining_capacity: max_capacity.map(|max| max.saturating_sub(current_tvl)),
+        }
+    }
+
+    /// Reject `amount` if it doesn't fit in the remaining capacity
+    pub fn check(&self, amount: U256) -> Result<(), YieldFarmingError> {
+        match self.remaining_capacity {
+            Some(remaining) if amount > remaining => Err(YieldFarmingError::PoolFull { amount, remaining }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Views that report a pool's total stake cap, in order of preference
+const CAPACITY_FUNCTIONS: [&str; 2] = ["cap", "maxTotalStake"];
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Get the deposit limits enforced before submitting a deposit.
+    ///
+    /// Limits configured on the builder take precedence; otherwise they are read
+    /// from the pool's `minStake()`/`maxStake()` views when its ABI has them, and
+    /// cached for a few minutes. A `maxStake()` of zero is treated as uncapped.
+    pub async fn deposit_limits(&self) -> Result<DepositLimits, YieldFarmingError> {
+        let abi = self.contract.abi();
+        let min = match (self.deposit_limits.min, abi.function("minStake")) {
+            (Some(min), _) => Some(min),
+            (None, Ok(_)) => Some(self.query_with_retry::<U256, _>("minStake", ()).await?),
+            (None, Err(_)) => None,
+        };
+        let max = match (self.deposit_limits.max, abi.function("maxStake")) {
+            (Some(max), _) => Some(max),
+            (None, Ok(_)) => Some(self.query_with_retry::<U256, _>("maxStake", ()).await?).filter(|max| !max.is_zero()),
+            (None, Err(_)) => None,
+        };
+
+        Ok(DepositLimits { min, max })
+    }
+
+    /// Get the pool's total stake cap and how much of it is left.
+    ///
+    /// Reads `cap()` or `maxTotalStake()`; pools with neither, or a cap of zero, are uncapped.
+    pub async fn pool_capacity(&self) -> Result<CapacityInfo, YieldFarmingError> {
+        let mut max_capacity = None;
+        for function in CAPACITY_FUNCTIONS {
+            if let Some(cap) = self.optional_view::<U256, _>(function, ()).await? {
+                max_capacity = Some(cap).filter(|cap| !cap.is_zero());
+                break;
+            }
+        }
+        let current_tvl = self.get_total_value_locked().await?;
+
+        Ok(CapacityInfo::new(max_capacity, current_tvl))
+    }
+
+    /// Reject `amount` with `PoolFull` if capacity checks are enabled and it wouldn't fit
+    pub(crate) async fn ensure_capacity(&self, amount: U256) -> Result<(), YieldFarmingError> {
+        if !self.check_capacity {
+            return Ok(());
+        }
+        self.pool_capacity().await?.check(amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_limits() {
+        let limits = DepositLimits {
+            min: Some(10.into()),
+            max: Some(100.into()),
+        };
+        assert!(limits.check(50.into()).is_ok());
+        assert!(matches!(limits.check(5.into()), Err(YieldFarmingError::AmountBelowMinimum { .. })));
+        assert!(matches!(limits.check(101.into()), Err(YieldFarmingError::AmountAboveMaximum { .. })));
+        assert!(DepositLimits::default().check(U256::MAX).is_ok());
+    }
+
+    #[test]
+    fn test_check_capacity() {
+        let capacity = CapacityInfo::new(Some(100.into()), 120.into());
+        assert_eq!(capacity.remaining_capacity, Some(U256::zero()));
+        assert!(matches!(capacity.check(1.into()), Err(YieldFarmingError::PoolFull { .. })));
+
+        let capacity = CapacityInfo::new(Some(100.into()), 60.into());
+        assert!(capacity.check(40.into()).is_ok());
+        assert!(capacity.check(41.into()).is_err());
+        assert!(CapacityInfo::new(None, 60.into()).check(U256::MAX).is_ok());
+    }
+}
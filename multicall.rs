@@ -0,0 +1,124 @@
+use web3::{
+    ethabi::{Contract as ContractABI, Token},
+    types::{Address, BlockId, BlockNumber, Bytes, CallRequest, H160, U256},
+    Transport,
+};
+
+use crate::{retry, revert, BatchResult, YieldFarmingClient, YieldFarmingError};
+
+/// Multicall3 deployment address, identical on mainnet and most other chains
+pub const MULTICALL3_ADDRESS: &str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+
+/// Multicall3 functions used for batched reads
+const MULTICALL3_ABI: &str = r#"[
+    {"type":"function","name":"aggregate3","stateMutability":"payable","inputs":[{"name":"calls","type":"tuple[]","components":[{"name":"target","type":"address"},{"name":"allowFailure","type":"bool"},{"name":"callData","type":"bytes"}]}],"outputs":[{"name":"returnData","type":"tuple[]","components":[{"name":"success","type":"bool"},{"name":"returnData","type":"bytes"}]}]}
+]"#;
+
+/// `MULTICALL3_ADDRESS` as an `Address`
+pub(crate) fn default_multicall_address() -> Address {
+    H160([
+        0xca, 0x11, 0xbd, 0xe0, 0x59, 0x77, 0xb3, 0x63, 0x11, 0x67, 0x02, 0x88, 0x62, 0xbe, 0x2a, 0x17, 0x39, 0x76, 0xca, 0x11,
+    ])
+}
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Get the staked balances of many accounts in a single `eth_call` through Multicall3.
+    ///
+    /// Results line up with `accounts`; an account whose `balanceOf` reverts doesn't affect the others.
+    pub async fn get_staked_balances(&self, accounts: &[Address]) -> Result<BatchResult<U256>, YieldFarmingError> {
+        let balance_of = self.contract.abi().function("balanceOf")?;
+        let calls = accounts
+            .iter()
+            .map(|account| Ok((self.contract.address(), balance_of.encode_input(&[Token::Address(*account)])?)))
+            .collect::<Result<Vec<_>, YieldFarmingError>>()?;
+
+        Ok(self.multicall(calls).await?.map(|output| match balance_of.decode_output(&output)?.into_iter().next() {
+            Some(Token::Uint(balance)) => Ok(balance),
+            _ => Err(YieldFarmingError::Contract(web3::contract::Error::InvalidOutputType(
+                "balanceOf did not return a uint256".into(),
+            ))),
+        }))
+    }
+
+    /// Execute `(target, call data)` pairs in one `aggregate3` call, returning each raw output in order.
+    ///
+    /// Inner calls are allowed to fail; a reverted call's entry is `Reverted` with its decoded reason.
+    pub(crate) async fn multicall(&self, calls: Vec<(Address, Vec<u8>)>) -> Result<BatchResult<Vec<u8>>, YieldFarmingError> {
+        if calls.is_empty() {
+            return Ok(Vec::new().into());
+        }
+
+        let abi = ContractABI::load(MULTICALL3_ABI.as_bytes())?;
+        let aggregate3 = abi.function("aggregate3")?;
+        let tokens = calls
+            .into_iter()
+            .map(|(target, data)| Token::Tuple(vec![Token::Address(target), Token::Bool(true), Token::Bytes(data)]))
+            .collect();
+        let request = CallRequest {
+            to: Some(self.multicall_address),
+            data: Some(Bytes(aggregate3.encode_input(&[Token::Array(tokens)])?)),
+            ..CallRequest::default()
+        };
+
+        let output = self.retry_policy
+            .retry(|| retry::with_timeout(self.request_timeout, async {
+                Ok(self.web3.eth().call(request.clone(), Some(BlockId::Number(BlockNumber::Latest))).await?)
+            }))
+            .await?;
+
+        let results = match aggregate3.decode_output(&output.0)?.into_iter().next() {
+            Some(Token::Array(results)) => results,
+            _ => return Err(invalid_multicall_output()),
+        };
+        Ok(results
+            .into_iter()
+            .map(|result| match result {
+                Token::Tuple(fields) => match fields.as_slice() {
+                    [Token::Bool(true), Token::Bytes(data)] => Ok(data.clone()),
+                    [Token::Bool(false), Token::Bytes(data)] => Err(revert::revert_error(self.contract.abi(), data)),
+                    _ => Err(invalid_multicall_output()),
+                },
+                _ => Err(invalid_multicall_output()),
+            })
+            .collect())
+    }
+}
+
+fn invalid_multicall_output() -> YieldFarmingError {
+    YieldFarmingError::Contract(web3::contract::Error::InvalidOutputType("unexpected aggregate3 output".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTransport;
+    use std::str::FromStr;
+    use web3::ethabi;
+
+    const POOL_ABI: &[u8] = br#"[
+        {"type":"function","name":"balanceOf","stateMutability":"view","inputs":[{"name":"account","type":"address"}],"outputs":[{"name":"","type":"uint256"}]}
+    ]"#;
+
+    #[test]
+    fn test_default_multicall_address() {
+        assert_eq!(default_multicall_address(), Address::from_str(MULTICALL3_ADDRESS).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_get_staked_balances() {
+        let transport = MockTransport::new();
+        let balance = |value: u64| Token::Tuple(vec![Token::Bool(true), Token::Bytes(ethabi::encode(&[Token::Uint(value.into())]))]);
+        let reverted = Token::Tuple(vec![Token::Bool(false), Token::Bytes(Vec::new())]);
+        transport.push_call_result(&[Token::Array(vec![balance(5), reverted, balance(7)])]);
+
+        let client = YieldFarmingClient::from_transport(transport.clone(), Address::repeat_byte(0x11), POOL_ABI).unwrap();
+        let balances = client
+            .get_staked_balances(&[Address::repeat_byte(0x01), Address::repeat_byte(0x02), Address::repeat_byte(0x03)])
+            .await
+            .unwrap();
+
+        assert!(matches!(balances[1], Err(YieldFarmingError::Reverted { reason: None })));
+        assert_eq!(balances.into_oks(), vec![U256::from(5), U256::from(7)]);
+        assert_eq!(transport.requests().len(), 1);
+    }
+}
@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use web3::{
+    api::Eth,
+    types::{Address, BlockNumber, U256},
+    Transport,
+};
+
+use crate::{retry, YieldFarmingError};
+
+/// Hands out sequential nonces so rapid back-to-back transactions don't collide.
+///
+/// Each account's nonce is seeded from `eth_getTransactionCount(account, pending)`
+/// on first use and incremented locally afterwards. This assumes the manager is
+/// the only signer submitting transactions for those accounts; anything sent
+/// from elsewhere makes the local counter stale until `reset` is called.
+#[derive(Debug, Default)]
+pub struct NonceManager {
+    next: Mutex<HashMap<Address, U256>>,
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reserve the next nonce for `account`
+    pub(crate) async fn next_nonce<T: Transport>(&self, eth: &Eth<T>, account: Address, timeout: Option<Duration>) -> Result<U256, YieldFarmingError> {
+        let mut next = self.next.lock().await;
+        let nonce = match next.get(&account) {
+            Some(nonce) => *nonce,
+            None => retry::with_timeout(timeout, async { Ok(eth.transaction_count(account, Some(BlockNumber::Pending)).await?) }).await?,
+        };
+        next.insert(account, nonce + 1);
+
+        Ok(nonce)
+    }
+
+    /// Forget the local nonce for `account` so the next call resyncs from the node
+    pub async fn reset(&self, account: Address) {
+        self.next.lock().await.remove(&account);
+    }
+
+    /// Forget all locally tracked nonces
+    pub async fn reset_all(&self) {
+        self.next.lock().await.clear();
+    }
+}
@@ -0,0 +1,82 @@
+//! Object-safe trait over the client's core operations, for generic code and test doubles.
+
+use futures::future::{BoxFuture, FutureExt};
+use web3::{
+    types::{Address, H256, U256},
+    Transport,
+};
+
+use crate::{YieldFarmingClient, YieldFarmingError};
+
+/// Core farming operations, implemented by `YieldFarmingClient` over any thread-safe transport.
+///
+/// Methods return boxed futures so the trait can be used as `dyn FarmingOps`,
+/// e.g. to swap a chain-backed client for a simulation or a test double.
+pub trait FarmingOps: Send + Sync {
+    /// Deposit `amount` staking tokens from `account`, returning the transaction hash
+    fn deposit(&self, amount: U256, account: Address) -> BoxFuture<'_, Result<H256, YieldFarmingError>>;
+
+    /// Withdraw `amount` staked tokens to `account`, returning the transaction hash
+    fn withdraw(&self, amount: U256, account: Address) -> BoxFuture<'_, Result<H256, YieldFarmingError>>;
+
+    /// Claim `account`'s pending rewards, returning the transaction hash
+    fn claim_rewards(&self, account: Address) -> BoxFuture<'_, Result<H256, YieldFarmingError>>;
+
+    /// Get `account`'s staked balance
+    fn get_staked_balance(&self, account: Address) -> BoxFuture<'_, Result<U256, YieldFarmingError>>;
+
+    /// Get `account`'s pending rewards
+    fn get_pending_rewards(&self, account: Address) -> BoxFuture<'_, Result<U256, YieldFarmingError>>;
+
+    /// Get the pool's total value locked
+    fn get_total_value_locked(&self) -> BoxFuture<'_, Result<U256, YieldFarmingError>>;
+}
+
+impl<T> FarmingOps for YieldFarmingClient<T>
+where
+    T: Transport + Send + Sync,
+    T::Out: Send,
+{
+    fn deposit(&self, amount: U256, account: Address) -> BoxFuture<'_, Result<H256, YieldFarmingError>> {
+        YieldFarmingClient::deposit(self, amount, account).boxed()
+    }
+
+    fn withdraw(&self, amount: U256, account: Address) -> BoxFuture<'_, Result<H256, YieldFarmingError>> {
+        YieldFarmingClient::withdraw(self, amount, account).boxed()
+    }
+
+    fn claim_rewards(&self, account: Address) -> BoxFuture<'_, Result<H256, YieldFarmingError>> {
+        YieldFarmingClient::claim_rewards(self, account).boxed()
+    }
+
+    fn get_staked_balance(&self, account: Address) -> BoxFuture<'_, Result<U256, YieldFarmingError>> {
+        YieldFarmingClient::get_staked_balance(self, account).boxed()
+    }
+
+    fn get_pending_rewards(&self, account: Address) -> BoxFuture<'_, Result<U256, YieldFarmingError>> {
+        YieldFarmingClient::get_pending_rewards(self, account).boxed()
+    }
+
+    fn get_total_value_locked(&self) -> BoxFuture<'_, Result<U256, YieldFarmingError>> {
+        YieldFarmingClient::get_total_value_locked(self).boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTransport;
+    use web3::ethabi::Token;
+
+    #[tokio::test]
+    async fn test_client_as_dyn_farming_ops() {
+        let transport = MockTransport::new();
+        transport.push_call_result(&[Token::Uint(42.into())]);
+        let abi = br#"[
+            {"type":"function","name":"balanceOf","stateMutability":"view","inputs":[{"name":"account","type":"address"}],"outputs":[{"name":"","type":"uint256"}]}
+        ]"#;
+
+        let ops: Box<dyn FarmingOps> = Box::new(YieldFarmingClient::from_transport(transport, Address::zero(), abi).unwrap());
+        assert_eq!(ops.get_staked_balance(Address::repeat_byte(0x01)).await.unwrap(), U256::from(42));
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use web3::{
+    contract::{Contract, Options},
+    transports::Http,
+    types::{Address, H160, U256},
+    Transport, Web3,
+};
+
+use crate::{fixed, retry, units, YieldFarmingClient, YieldFarmingError};
+
+/// Placeholder address under which oracles price the chain's native currency (ETH on mainnet)
+pub const NATIVE_TOKEN: Address = H160([0xee; 20]);
+
+/// Source of USD token prices used for valuation
+pub trait PriceOracle {
+    /// Price of one whole unit of `token` in USD
+    fn price_usd(&self, token: Address) -> impl Future<Output = Result<f64, YieldFarmingError>> + Send;
+}
+
+/// Oldest round `ChainlinkOracle` accepts unless configured otherwise, matching the longest common feed heartbeat
+pub const DEFAULT_HEARTBEAT: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Chainlink aggregator functions used by `ChainlinkOracle`
+const AGGREGATOR_ABI: &str = r#"[
+    {"type":"function","name":"decimals","stateMutability":"view","inputs":[],"outputs":[{"name":"","type":"uint8"}]},
+    {"type":"function","name":"latestRoundData","stateMutability":"view","inputs":[],"outputs":[{"name":"roundId","type":"uint80"},{"name":"answer","type":"int256"},{"name":"startedAt","type":"uint256"},{"name":"updatedAt","type":"uint256"},{"name":"answeredInRound","type":"uint80"}]}
+]"#;
+
+/// Prices tokens using Chainlink USD aggregators, one feed per token
+#[derive(Debug, Clone)]
+pub struct ChainlinkOracle<T: Transport = Http> {
+    web3: Web3<T>,
+    feeds: HashMap<Address, Address>,
+    request_timeout: Option<Duration>,
+    heartbeat: Duration,
+}
+
+impl ChainlinkOracle<Http> {
+    pub fn new(rpc_url: &str) -> Result<Self, YieldFarmingError> {
+        Ok(Self::from_transport(Http::new(rpc_url)?))
+    }
+}
+
+impl<T: Transport> ChainlinkOracle<T> {
+    /// Create an oracle on top of an existing transport
+    pub fn from_transport(transport: T) -> Self {
+        Self {
+            web3: Web3::new(transport),
+            feeds: HashMap::new(),
+            request_timeout: None,
+            heartbeat: DEFAULT_HEARTBEAT,
+        }
+    }
+
+    /// Set the timeout applied to every feed read, failing it with `Timeout`
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Reject rounds last updated more than `heartbeat` ago with `InvalidPrice`
+    pub fn with_heartbeat(mut self, heartbeat: Duration) -> Self {
+        self.heartbeat = heartbeat;
+        self
+    }
+
+    /// Register the `TOKEN / USD` aggregator used to price `token`
+    pub fn with_feed(mut self, token: Address, aggregator: Address) -> Self {
+        self.feeds.insert(token, aggregator);
+        self
+    }
+
+    async fn latest_price(&self, token: Address) -> Result<f64, YieldFarmingError> {
+        let aggregator = *self.feeds.get(&token).ok_or(YieldFarmingError::NoPriceFeed(token))?;
+        let feed = Contract::from_json(self.web3.eth(), aggregator, AGGREGATOR_ABI.as_bytes())?;
+
+        let decimals: u8 = retry::with_timeout(self.request_timeout, async {
+            Ok(feed.query("decimals", (), None, Options::default(), None).await?)
+        })
+        .await?;
+        let (round_id, answer, _started_at, updated_at, answered_in_round): (U256, U256, U256, U256, U256) =
+            retry::with_timeout(self.request_timeout, async {
+                Ok(feed.query("latestRoundData", (), None, Options::default(), None).await?)
+            })
+            .await?;
+
+        // `answer` is an int256; a set sign bit means the feed reported a negative price
+        if answer.bit(255) {
+            return Err(YieldFarmingError::InvalidPrice(token));
+        }
+        if !round_is_fresh(round_id, updated_at, answered_in_round, self.heartbeat) {
+            return Err(YieldFarmingError::InvalidPrice(token));
+        }
+
+        Ok(units::from_wei(answer, decimals))
+    }
+}
+
+/// Whether a round is complete, was answered in itself, and was updated within `heartbeat` of now
+fn round_is_fresh(round_id: U256, updated_at: U256, answered_in_round: U256, heartbeat: Duration) -> bool {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    !updated_at.is_zero()
+        && answered_in_round >= round_id
+        && updated_at <= U256::from(now)
+        && now - updated_at.low_u64() <= heartbeat.as_secs()
+}
+
+impl<T> PriceOracle for ChainlinkOracle<T>
+where
+    T: Transport + Send + Sync,
+    T::Out: Send,
+{
+    async fn price_usd(&self, token: Address) -> Result<f64, YieldFarmingError> {
+        self.latest_price(token).await
+    }
+}
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Get total value locked in USD
+    pub async fn get_tvl_usd(&self, oracle: &impl PriceOracle) -> Result<f64, YieldFarmingError> {
+        let (tvl, staking_token, decimals) = futures::try_join!(
+            self.get_total_value_locked(),
+            self.staking_token(),
+            self.token_decimals(),
+        )?;
+        let price = oracle.price_usd(staking_token).await?;
+
+        Ok(units::from_wei(tvl, decimals) * price)
+    }
+
+    /// Get a user's pending rewards in USD
+    pub async fn get_pending_rewards_usd(&self, account: Address, oracle: &impl PriceOracle) -> Result<f64, YieldFarmingError> {
+        let (pending, reward_token) = futures::try_join!(
+            self.get_pending_rewards(account),
+            self.reward_token(),
+        )?;
+        let (decimals, price) = futures::try_join!(self.reward_token_decimals(), oracle.price_usd(reward_token))?;
+
+        Ok(units::from_wei(pending, decimals) * price)
+    }
+
+    /// Get the USD value of `forecast_rewards` at the reward token's current price
+    pub async fn forecast_rewards_usd(&self, account: Address, horizon: Duration, oracle: &impl PriceOracle) -> Result<f64, YieldFarmingError> {
+        let (forecast, reward_token) = futures::try_join!(
+            self.forecast_rewards(account, horizon),
+            self.reward_token(),
+        )?;
+        let (decimals, price) = futures::try_join!(self.reward_token_decimals(), oracle.price_usd(reward_token))?;
+
+        Ok(fixed::to_f64(fixed::mul(fixed::from_base_units(forecast, decimals), fixed::from_f64(price))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedOracle(f64);
+
+    impl PriceOracle for FixedOracle {
+        async fn price_usd(&self, _token: Address) -> Result<f64, YieldFarmingError> {
+            Ok(self.0)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_oracle() {
+        let oracle = FixedOracle(1.25);
+        assert_eq!(oracle.price_usd(Address::zero()).await.unwrap(), 1.25);
+    }
+
+    #[tokio::test]
+    async fn test_missing_feed() {
+        let oracle = ChainlinkOracle::new("http://localhost:8545").unwrap();
+        let result = oracle.price_usd(Address::zero()).await;
+        assert!(matches!(result, Err(YieldFarmingError::NoPriceFeed(_))));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_stale_rounds() {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let round = |round_id: u64, updated_at: u64, answered_in_round: u64| {
+            [round_id, 200_000_000_000, updated_at, updated_at, answered_in_round].map(|v| web3::ethabi::Token::Uint(v.into()))
+        };
+        let transport = crate::testing::MockTransport::new();
+        for (round_id, updated_at, answered_in_round) in [(7, now - 60, 7), (7, now - 7200, 7), (7, now - 60, 6), (7, 0, 7)] {
+            transport.push_call_result(&[web3::ethabi::Token::Uint(8.into())]);
+            transport.push_call_result(&round(round_id, updated_at, answered_in_round));
+        }
+        let oracle = ChainlinkOracle::from_transport(transport)
+            .with_feed(Address::zero(), Address::repeat_byte(1))
+            .with_heartbeat(Duration::from_secs(3600));
+
+        assert_eq!(oracle.price_usd(Address::zero()).await.unwrap(), 2000.0);
+        for _ in 0..3 {
+            assert!(matches!(oracle.price_usd(Address::zero()).await, Err(YieldFarmingError::InvalidPrice(_))));
+        }
+    }
+}
@@ -0,0 +1,338 @@
+use std::sync::Mutex;
+use std::time::Instant;
+use web3::{
+    types::{Address, BlockNumber, Transaction, TransactionId, TransactionReceipt, TransactionRequest, H256, U256},
+    Transport,
+};
+
+use crate::{retry, telemetry, ConfirmedReceipt, GasConfig, YieldFarmingClient, YieldFarmingError, DEFAULT_MAX_POLL_ATTEMPTS, DEFAULT_POLL_INTERVAL};
+
+/// Gas used by a plain ether transfer
+const TRANSFER_GAS: u64 = 21_000;
+
+/// Where a submitted transaction currently stands
+#[derive(Debug, Clone, PartialEq)]
+pub enum TxStatus {
+    /// Not mined yet, or not yet seen by the node
+    Pending,
+    /// Mined and succeeded
+    Mined(TransactionReceipt),
+    /// Mined but reverted
+    Reverted(TransactionReceipt),
+    /// Its nonce was used by another transaction, so it will never be mined
+    Dropped,
+}
+
+/// Handle for tracking a submitted transaction to completion
+#[derive(Debug)]
+pub struct PendingTransaction<'a, T: Transport> {
+    client: &'a YieldFarmingClient<T>,
+    tx_hash: H256,
+    /// Sender and nonce, learned the first time the node returns the transaction
+    sender: Mutex<Option<(Address, U256)>>,
+}
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Track a transaction returned by one of the write methods
+    pub fn track(&self, tx_hash: H256) -> PendingTransaction<'_, T> {
+        PendingTransaction {
+            client: self,
+            tx_hash,
+            sender: Mutex::new(None),
+        }
+    }
+
+    /// Like `deposit`, but returning a handle to track the transaction with
+    pub async fn deposit_tracked(&self, amount: U256, account: Address) -> Result<PendingTransaction<'_, T>, YieldFarmingError> {
+        Ok(self.track(self.deposit(amount, account).await?))
+    }
+
+    /// Like `withdraw`, but returning a handle to track the transaction with
+    pub async fn withdraw_tracked(&self, amount: U256, account: Address) -> Result<PendingTransaction<'_, T>, YieldFarmingError> {
+        Ok(self.track(self.withdraw(amount, account).await?))
+    }
+
+    /// Like `claim_rewards`, but returning a handle to track the transaction with
+    pub async fn claim_rewards_tracked(&self, account: Address) -> Result<PendingTransaction<'_, T>, YieldFarmingError> {
+        Ok(self.track(self.claim_rewards(account).await?))
+    }
+
+    /// Resubmit a pending transaction's call with the same nonce and higher fees.
+    ///
+    /// Both EIP-1559 fees in `new_fees` must be set and at least 10% above the
+    /// original's, or nodes reject the replacement.
+    pub async fn speed_up(&self, tx: &PendingTransaction<'_, T>, new_fees: GasConfig) -> Result<PendingTransaction<'_, T>, YieldFarmingError> {
+        let original = self.replaceable_transaction(tx.tx_hash).await?;
+        let (max_fee_per_gas, max_priority_fee_per_gas) = match (new_fees.max_fee_per_gas, new_fees.max_priority_fee_per_gas) {
+            (Some(max_fee), Some(priority_fee)) => (max_fee, priority_fee),
+            _ => return Err(YieldFarmingError::MissingField("max_fee_per_gas and max_priority_fee_per_gas")),
+        };
+        let (old_max_fee, old_priority_fee) = fee_caps(&original);
+        if !is_sufficient_bump(old_max_fee, max_fee_per_gas) || !is_sufficient_bump(old_priority_fee, max_priority_fee_per_gas) {
+            return Err(YieldFarmingError::ReplacementUnderpriced);
+        }
+
+        let request = TransactionRequest {
+            from: original.from.unwrap_or_default(),
+            to: original.to,
+            gas: Some(new_fees.gas_limit.unwrap_or(original.gas)),
+            value: Some(original.value),
+            data: Some(original.input),
+            nonce: Some(original.nonce),
+            transaction_type: Some(2u64.into()),
+            max_fee_per_gas: Some(max_fee_per_gas),
+            max_priority_fee_per_gas: Some(max_priority_fee_per_gas),
+            ..TransactionRequest::default()
+        };
+        self.send_replacement(request).await
+    }
+
+    /// Cancel a pending transaction by sending a zero-value transfer to its sender at the same nonce,
+    /// with both fees bumped by the minimum nodes accept
+    pub async fn cancel(&self, tx: &PendingTransaction<'_, T>) -> Result<PendingTransaction<'_, T>, YieldFarmingError> {
+        let original = self.replaceable_transaction(tx.tx_hash).await?;
+        let from = original.from.unwrap_or_default();
+        let (old_max_fee, old_priority_fee) = fee_caps(&original);
+
+        let request = TransactionRequest {
+            from,
+            to: Some(from),
+            gas: Some(TRANSFER_GAS.into()),
+            value: Some(U256::zero()),
+            nonce: Some(original.nonce),
+            transaction_type: Some(2u64.into()),
+            max_fee_per_gas: Some(minimum_bump(old_max_fee)),
+            max_priority_fee_per_gas: Some(minimum_bump(old_priority_fee)),
+            ..TransactionRequest::default()
+        };
+        self.send_replacement(request).await
+    }
+
+    /// Wait for `tx_hash` to be mined by `valid_until`, cancelling it and failing with `Expired` if it isn't.
+    ///
+    /// Returns the hash straight away without a deadline. The cancellation is a
+    /// race: if the original is mined first, the cancel fails at the node and the
+    /// original's hash is returned; if the cancel is sent but the original still
+    /// wins, `Expired` is returned for a transaction that did go through.
+    pub(crate) async fn enforce_deadline(&self, tx_hash: H256, valid_until: Option<Instant>) -> Result<H256, YieldFarmingError> {
+        let Some(valid_until) = valid_until else {
+            return Ok(tx_hash);
+        };
+        let pending = self.track(tx_hash);
+        loop {
+            match pending.status().await? {
+                TxStatus::Mined(_) | TxStatus::Reverted(_) => return Ok(tx_hash),
+                TxStatus::Dropped => return Err(YieldFarmingError::TransactionDropped(tx_hash)),
+                TxStatus::Pending => {}
+            }
+
+            let now = Instant::now();
+            if now >= valid_until {
+                return match self.cancel(&pending).await {
+                    Ok(_) => Err(YieldFarmingError::Expired(tx_hash)),
+                    Err(YieldFarmingError::AlreadyMined(_)) => Ok(tx_hash),
+                    Err(err) => Err(err),
+                };
+            }
+            tokio::time::sleep(DEFAULT_POLL_INTERVAL.min(valid_until - now)).await;
+        }
+    }
+
+    /// Fetch a transaction that is known to the node but not yet mined
+    async fn replaceable_transaction(&self, tx_hash: H256) -> Result<Transaction, YieldFarmingError> {
+        let transaction = retry::with_timeout(self.request_timeout, async {
+            Ok(self.web3.eth().transaction(TransactionId::Hash(tx_hash)).await?)
+        })
+        .await?
+        .ok_or(YieldFarmingError::TransactionNotFound(tx_hash))?;
+        if transaction.block_number.is_some() {
+            return Err(YieldFarmingError::AlreadyMined(tx_hash));
+        }
+        Ok(transaction)
+    }
+
+    async fn send_replacement(&self, request: TransactionRequest) -> Result<PendingTransaction<'_, T>, YieldFarmingError> {
+        let tx_hash = telemetry::rpc("eth_sendTransaction", retry::with_timeout(self.request_timeout, async {
+            self.web3.eth().send_transaction(request).await.map_err(|err| self.rpc_error(err))
+        }))
+        .await?;
+        telemetry::tx_submitted();
+        Ok(self.track(tx_hash))
+    }
+}
+
+/// Max fee and priority fee of a transaction; legacy transactions pay `gas_price` for both
+fn fee_caps(transaction: &Transaction) -> (U256, U256) {
+    let gas_price = transaction.gas_price.unwrap_or_default();
+    (
+        transaction.max_fee_per_gas.unwrap_or(gas_price),
+        transaction.max_priority_fee_per_gas.unwrap_or(gas_price),
+    )
+}
+
+/// Whether `new` is at least 10% above `old`, the minimum replacement bump nodes accept
+fn is_sufficient_bump(old: U256, new: U256) -> bool {
+    new.saturating_mul(10.into()) >= old.saturating_mul(11.into())
+}
+
+/// Smallest fee that is a sufficient bump over `old`
+fn minimum_bump(old: U256) -> U256 {
+    let bumped = old.saturating_mul(11.into());
+    let (quotient, remainder) = bumped.div_mod(10.into());
+    if remainder.is_zero() { quotient } else { quotient + 1 }
+}
+
+impl<T: Transport> PendingTransaction<'_, T> {
+    pub fn tx_hash(&self) -> H256 {
+        self.tx_hash
+    }
+
+    /// Check the transaction's current status.
+    ///
+    /// A transaction is reported `Dropped` once the node no longer knows it and
+    /// its sender's mined transaction count has moved past its nonce, meaning a
+    /// replacement was mined instead.
+    pub async fn status(&self) -> Result<TxStatus, YieldFarmingError> {
+        let eth = self.client.web3.eth();
+        let timeout = self.client.request_timeout;
+
+        if let Some(receipt) = retry::with_timeout(timeout, async { Ok(eth.transaction_receipt(self.tx_hash).await?) }).await? {
+            if receipt.block_number.is_some() {
+                return Ok(match receipt.status {
+                    Some(status) if status.is_zero() => TxStatus::Reverted(receipt),
+                    _ => TxStatus::Mined(receipt),
+                });
+            }
+        }
+
+        let transaction = retry::with_timeout(timeout, async { Ok(eth.transaction(TransactionId::Hash(self.tx_hash)).await?) }).await?;
+        if let Some(transaction) = transaction {
+            if let Some(from) = transaction.from {
+                *self.sender.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some((from, transaction.nonce));
+            }
+            return Ok(TxStatus::Pending);
+        }
+
+        let sender = *self.sender.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some((from, nonce)) = sender {
+            let mined_count = retry::with_timeout(timeout, async { Ok(eth.transaction_count(from, Some(BlockNumber::Latest)).await?) }).await?;
+            if mined_count > nonce {
+                return Ok(TxStatus::Dropped);
+            }
+        }
+
+        Ok(TxStatus::Pending)
+    }
+
+    /// Poll until the transaction succeeds under `confirmations` blocks, reverts or is dropped
+    pub async fn await_final(&self, confirmations: u64) -> Result<ConfirmedReceipt, YieldFarmingError> {
+        for _ in 0..DEFAULT_MAX_POLL_ATTEMPTS {
+            match self.status().await? {
+                TxStatus::Mined(_) => {
+                    return self.client
+                        .wait_for_confirmation(self.tx_hash, confirmations, DEFAULT_POLL_INTERVAL, DEFAULT_MAX_POLL_ATTEMPTS)
+                        .await;
+                }
+                TxStatus::Reverted(_) => {
+                    telemetry::tx_reverted();
+                    return Err(YieldFarmingError::TransactionReverted(self.tx_hash));
+                }
+                TxStatus::Dropped => return Err(YieldFarmingError::TransactionDropped(self.tx_hash)),
+                TxStatus::Pending => tokio::time::sleep(DEFAULT_POLL_INTERVAL).await,
+            }
+        }
+
+        Err(YieldFarmingError::Timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTransport;
+
+    fn client(transport: &MockTransport) -> YieldFarmingClient<MockTransport> {
+        YieldFarmingClient::from_transport(transport.clone(), Address::zero(), b"[]").unwrap()
+    }
+
+    #[test]
+    fn test_fee_bump() {
+        assert!(is_sufficient_bump(100.into(), 110.into()));
+        assert!(!is_sufficient_bump(100.into(), 109.into()));
+        assert_eq!(minimum_bump(100.into()), U256::from(110));
+        assert_eq!(minimum_bump(101.into()), U256::from(112));
+        assert!(is_sufficient_bump(101.into(), minimum_bump(101.into())));
+    }
+
+    #[tokio::test]
+    async fn test_claim_rewards_tracked() {
+        let transport = MockTransport::new();
+        let tx_hash = H256::repeat_byte(0xaa);
+        transport.set_response("eth_estimateGas", serde_json::json!("0x5208"));
+        transport.set_response("eth_sendTransaction", serde_json::to_value(tx_hash).unwrap());
+        let abi = br#"[{"type":"function","name":"claimRewards","stateMutability":"nonpayable","inputs":[],"outputs":[]}]"#;
+
+        let client = YieldFarmingClient::from_transport(transport, Address::zero(), abi).unwrap();
+        let pending = client.claim_rewards_tracked(Address::repeat_byte(0x01)).await.unwrap();
+        assert_eq!(pending.tx_hash(), tx_hash);
+    }
+
+    #[tokio::test]
+    async fn test_status_reverted() {
+        let transport = MockTransport::new();
+        let receipt = TransactionReceipt {
+            block_number: Some(10u64.into()),
+            status: Some(0u64.into()),
+            ..TransactionReceipt::default()
+        };
+        transport.set_response("eth_getTransactionReceipt", serde_json::to_value(&receipt).unwrap());
+
+        let client = client(&transport);
+        let status = client.track(H256::zero()).status().await.unwrap();
+        assert_eq!(status, TxStatus::Reverted(receipt));
+    }
+
+    #[tokio::test]
+    async fn test_status_dropped_after_nonce_reused() {
+        let transport = MockTransport::new();
+        transport.set_response("eth_getTransactionReceipt", serde_json::Value::Null);
+        transport.push_response(
+            "eth_getTransactionByHash",
+            serde_json::json!({
+                "hash": H256::zero(), "nonce": "0x5", "from": Address::repeat_byte(0x22),
+                "value": "0x0", "gas": "0x5208", "input": "0x",
+            }),
+        );
+        transport.set_response("eth_getTransactionByHash", serde_json::Value::Null);
+        transport.set_response("eth_getTransactionCount", serde_json::json!("0x6"));
+
+        let client = client(&transport);
+        let pending = client.track(H256::zero());
+        assert_eq!(pending.status().await.unwrap(), TxStatus::Pending);
+        assert_eq!(pending.status().await.unwrap(), TxStatus::Dropped);
+    }
+
+    #[tokio::test]
+    async fn test_enforce_deadline_cancels_unmined_transaction() {
+        let transport = MockTransport::new();
+        let sender = Address::repeat_byte(0x22);
+        transport.set_response("eth_getTransactionReceipt", serde_json::Value::Null);
+        transport.set_response(
+            "eth_getTransactionByHash",
+            serde_json::json!({
+                "hash": H256::zero(), "nonce": "0x5", "from": sender, "to": Address::repeat_byte(0x11),
+                "value": "0x0", "gas": "0x5208", "gasPrice": "0x64", "input": "0x",
+            }),
+        );
+        transport.set_response("eth_sendTransaction", serde_json::to_value(H256::repeat_byte(0xcc)).unwrap());
+
+        let client = client(&transport);
+        assert_eq!(client.enforce_deadline(H256::zero(), None).await.unwrap(), H256::zero());
+        let result = client.enforce_deadline(H256::zero(), Some(Instant::now())).await;
+        assert!(matches!(result, Err(YieldFarmingError::Expired(_))));
+
+        let sends: Vec<_> = transport.requests().into_iter().filter(|(method, _)| method == "eth_sendTransaction").collect();
+        assert_eq!(sends[0].1[0]["to"], serde_json::to_value(sender).unwrap());
+        assert_eq!(sends[0].1[0]["nonce"], "0x5");
+    }
+}
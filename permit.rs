@@ -0,0 +1,153 @@
+use web3::{
+    contract::Options,
+    ethabi::{self, Token},
+    signing::keccak256,
+    types::{Address, H256, U256},
+    Transport,
+};
+
+use crate::{erc20, retry, trace, GasConfig, YieldFarmingClient, YieldFarmingError};
+
+/// `keccak256("Permit(address owner,address spender,uint256 value,uint256 nonce,uint256 deadline)")`
+pub const PERMIT_TYPEHASH: H256 = H256([
+    0x6e, 0x71, 0xed, 0xae, 0x12, 0xb1, 0xb9, 0x7f, 0x4d, 0x1f, 0x60, 0x37, 0x0f, 0xef, 0x10, 0x10,
+    0x5f, 0xa2, 0xfa, 0xae, 0x01, 0x26, 0x11, 0x4a, 0x16, 0x9c, 0x64, 0x84, 0x5d, 0x61, 0x26, 0xc9,
+]);
+
+/// Pool functions that take an EIP-2612 permit alongside the deposit, as `(amount, deadline, v, r, s)`
+const PERMIT_DEPOSIT_FUNCTIONS: [&str; 2] = ["depositWithPermit", "stakeWithPermit"];
+
+/// An ECDSA signature over a permit digest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PermitSignature {
+    /// Recovery id, 27 or 28
+    pub v: u8,
+    pub r: H256,
+    pub s: H256,
+}
+
+/// EIP-712 digest an owner signs to approve `spender` for `value` via EIP-2612 `permit`
+fn encode_permit_digest(domain_separator: H256, owner: Address, spender: Address, value: U256, nonce: U256, deadline: U256) -> H256 {
+    let struct_hash = keccak256(&ethabi::encode(&[
+        Token::FixedBytes(PERMIT_TYPEHASH.as_bytes().to_vec()),
+        Token::Address(owner),
+        Token::Address(spender),
+        Token::Uint(value),
+        Token::Uint(nonce),
+        Token::Uint(deadline),
+    ]));
+
+    let mut message = Vec::with_capacity(66);
+    message.extend_from_slice(b"\x19\x01");
+    message.extend_from_slice(domain_separator.as_bytes());
+    message.extend_from_slice(&struct_hash);
+    H256(keccak256(&message))
+}
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Build the digest `owner` must sign to permit the pool to pull `amount` staking tokens until `deadline`.
+    ///
+    /// Reads the staking token's `DOMAIN_SEPARATOR()` and the owner's current `nonces(owner)`,
+    /// so the digest is invalidated by any other permit the owner signs first.
+    pub async fn permit_digest(&self, owner: Address, amount: U256, deadline: U256) -> Result<H256, YieldFarmingError> {
+        let token = self.staking_token().await?;
+        let token_contract = erc20::erc20_contract(self.web3.eth(), token)?;
+        let (domain_separator, nonce): (H256, U256) = futures::try_join!(
+            retry::with_timeout(self.request_timeout, async {
+                Ok(token_contract.query("DOMAIN_SEPARATOR", (), None, Options::default(), None).await?)
+            }),
+            retry::with_timeout(self.request_timeout, async {
+                Ok(token_contract.query("nonces", (owner,), None, Options::default(), None).await?)
+            }),
+        )?;
+
+        Ok(encode_permit_digest(domain_separator, owner, self.contract.address(), amount, nonce, deadline))
+    }
+
+    /// Deposit using an EIP-2612 permit, so approval and deposit happen in one transaction.
+    ///
+    /// `signature` is the owner's signature over `permit_digest(account, amount, deadline)`.
+    /// Requires the pool to expose `depositWithPermit` or `stakeWithPermit`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(%amount, ?account), err))]
+    pub async fn deposit_with_permit(
+        &self,
+        amount: U256,
+        account: Address,
+        deadline: U256,
+        signature: PermitSignature,
+    ) -> Result<H256, YieldFarmingError> {
+        self.deposit_with_permit_with_gas(amount, account, deadline, signature, &GasConfig::default()).await
+    }
+
+    /// Deposit using an EIP-2612 permit with explicit gas settings, checking pause state if `gas` asks to
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(%amount, ?account), err))]
+    pub async fn deposit_with_permit_with_gas(
+        &self,
+        amount: U256,
+        account: Address,
+        deadline: U256,
+        signature: PermitSignature,
+        gas: &GasConfig,
+    ) -> Result<H256, YieldFarmingError> {
+        let abi = self.contract.abi();
+        let func = PERMIT_DEPOSIT_FUNCTIONS
+            .into_iter()
+            .find(|name| abi.function(name).is_ok())
+            .ok_or(YieldFarmingError::PermitUnsupported)?;
+        self.ensure_not_paused(gas).await?;
+        self.deposit_limits().await?.check(amount)?;
+        self.ensure_capacity(amount).await?;
+
+        trace::debug!(func, "depositing with permit");
+        let params = (amount, deadline, signature.v, signature.r, signature.s);
+        let options = self.options_with_gas_limit(func, params, account, gas).await?;
+        let tx_hash = self.submit(func, params, account, options).await?;
+        self.enforce_deadline(tx_hash, gas.valid_until).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTransport;
+
+    #[test]
+    fn test_permit_typehash() {
+        let typehash = keccak256(b"Permit(address owner,address spender,uint256 value,uint256 nonce,uint256 deadline)");
+        assert_eq!(PERMIT_TYPEHASH, H256(typehash));
+    }
+
+    #[test]
+    fn test_permit_digest_is_deterministic() {
+        let digest = |nonce: u64| {
+            encode_permit_digest(H256::repeat_byte(0x01), Address::repeat_byte(0x02), Address::repeat_byte(0x03), 1_000.into(), nonce.into(), U256::MAX)
+        };
+        assert_eq!(digest(0), digest(0));
+        assert_ne!(digest(0), digest(1));
+    }
+
+    #[tokio::test]
+    async fn test_deposit_with_permit_unsupported() {
+        let client = YieldFarmingClient::from_transport(MockTransport::new(), Address::zero(), b"[]").unwrap();
+        let signature = PermitSignature { v: 27, r: H256::zero(), s: H256::zero() };
+        let result = client.deposit_with_permit(1.into(), Address::zero(), U256::MAX, signature).await;
+        assert!(matches!(result, Err(YieldFarmingError::PermitUnsupported)));
+    }
+
+    #[tokio::test]
+    async fn test_deposit_with_permit_rejects_paused_pool() {
+        const ABI: &[u8] = br#"[
+            {"type":"function","name":"depositWithPermit","stateMutability":"nonpayable","inputs":[{"name":"amount","type":"uint256"},{"name":"deadline","type":"uint256"},{"name":"v","type":"uint8"},{"name":"r","type":"bytes32"},{"name":"s","type":"bytes32"}],"outputs":[]},
+            {"type":"function","name":"paused","stateMutability":"view","inputs":[],"outputs":[{"name":"","type":"bool"}]}
+        ]"#;
+        let transport = MockTransport::new();
+        transport.push_call_result(&[Token::Bool(true)]);
+        let client = YieldFarmingClient::from_transport(transport.clone(), Address::zero(), ABI).unwrap();
+
+        let signature = PermitSignature { v: 27, r: H256::zero(), s: H256::zero() };
+        let gas = GasConfig { check_paused: true, ..GasConfig::default() };
+        let result = client.deposit_with_permit_with_gas(1.into(), Address::zero(), U256::MAX, signature, &gas).await;
+        assert!(matches!(result, Err(YieldFarmingError::PoolPaused)));
+        assert_eq!(transport.requests().len(), 1);
+    }
+}
@@ -0,0 +1,73 @@
+use web3::{
+    contract::Options,
+    ethabi::{Function, Token},
+    types::{Address, H256},
+    Transport,
+};
+
+use crate::{YieldFarmingClient, YieldFarmingError};
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Send a transaction calling any function in the pool's ABI, for calls the typed methods don't cover.
+    ///
+    /// `params` are checked against the function's inputs before anything is sent.
+    pub async fn call_raw(&self, function: &str, params: Vec<Token>, account: Address, options: Options) -> Result<H256, YieldFarmingError> {
+        let data = self.raw_function(function, &params)?.encode_input(&params)?;
+        self.send_calldata(data, account, options).await
+    }
+
+    /// Query any view in the pool's ABI at the latest block and return its decoded outputs
+    pub async fn query_raw(&self, function: &str, params: Vec<Token>) -> Result<Vec<Token>, YieldFarmingError> {
+        let function = self.raw_function(function, &params)?;
+        self.call_view(function, &params).await
+    }
+
+    /// The overload of `name` taking as many inputs as `params` has
+    fn raw_function(&self, name: &str, params: &[Token]) -> Result<&Function, YieldFarmingError> {
+        let overloads = self.contract.abi()
+            .functions_by_name(name)
+            .map_err(|_| YieldFarmingError::UnknownFunction(name.to_string()))?;
+        overloads
+            .iter()
+            .find(|function| function.inputs.len() == params.len())
+            .ok_or_else(|| YieldFarmingError::ArgumentCountMismatch {
+                function: name.to_string(),
+                expected: overloads[0].inputs.len(),
+                actual: params.len(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTransport;
+    use web3::types::U256;
+
+    const POOL_ABI: &[u8] = br#"[
+        {"type":"function","name":"boostOf","stateMutability":"view","inputs":[{"name":"account","type":"address"}],"outputs":[{"name":"","type":"uint256"}]}
+    ]"#;
+
+    #[tokio::test]
+    async fn test_query_raw_decodes_outputs() {
+        let transport = MockTransport::new();
+        transport.push_call_result(&[Token::Uint(150.into())]);
+
+        let client = YieldFarmingClient::from_transport(transport, Address::zero(), POOL_ABI).unwrap();
+        let output = client.query_raw("boostOf", vec![Token::Address(Address::repeat_byte(0x01))]).await.unwrap();
+        assert_eq!(output, vec![Token::Uint(U256::from(150))]);
+    }
+
+    #[tokio::test]
+    async fn test_raw_calls_are_validated_before_sending() {
+        let transport = MockTransport::new();
+        let client = YieldFarmingClient::from_transport(transport.clone(), Address::zero(), POOL_ABI).unwrap();
+
+        let unknown = client.query_raw("boost", vec![]).await;
+        assert!(matches!(unknown, Err(YieldFarmingError::UnknownFunction(ref name)) if name == "boost"));
+
+        let miscounted = client.call_raw("boostOf", vec![], Address::zero(), Options::default()).await;
+        assert!(matches!(miscounted, Err(YieldFarmingError::ArgumentCountMismatch { expected: 1, actual: 0, .. })));
+        assert!(transport.requests().is_empty());
+    }
+}
@@ -0,0 +1,93 @@
+use web3::types::{Address, TransactionReceipt, H256, U256};
+
+use crate::events::{self, EventSignatures, FarmEvent};
+use crate::YieldFarmingError;
+
+/// A mined transaction's receipt with the fields every mined receipt has made non-optional
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfirmedReceipt {
+    pub tx_hash: H256,
+    /// Whether the transaction succeeded rather than reverted
+    pub status: bool,
+    pub gas_used: U256,
+    pub block_number: u64,
+    /// Price paid per unit of gas, zero on nodes that predate EIP-1559 receipts
+    pub effective_gas_price: U256,
+    /// Pool events emitted by the transaction, in log order
+    pub events: Vec<FarmEvent>,
+    /// The node's receipt, for logs emitted by other contracts such as token transfers
+    pub raw: TransactionReceipt,
+}
+
+impl ConfirmedReceipt {
+    /// Wrap a receipt, decoding the events `pool` emitted, or `None` if it isn't mined yet
+    pub(crate) fn from_mined(receipt: TransactionReceipt, pool: Address, signatures: &EventSignatures) -> Option<Self> {
+        let block_number = receipt.block_number?.as_u64();
+        let events = receipt
+            .logs
+            .iter()
+            .filter(|log| log.address == pool)
+            .filter_map(|log| events::decode_log_with(log, signatures).ok())
+            .collect();
+
+        Some(Self {
+            tx_hash: receipt.transaction_hash,
+            status: receipt.status == Some(1u64.into()),
+            gas_used: receipt.gas_used.unwrap_or_default(),
+            block_number,
+            effective_gas_price: receipt.effective_gas_price.unwrap_or_default(),
+            events,
+            raw: receipt,
+        })
+    }
+
+    /// Total gas cost in wei
+    pub fn fee(&self) -> Result<U256, YieldFarmingError> {
+        self.gas_used.checked_mul(self.effective_gas_price).ok_or(YieldFarmingError::ArithmeticOverflow("transaction fee"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use web3::types::{Bytes, Log};
+
+    #[test]
+    fn test_from_mined_decodes_pool_events() {
+        let pool = Address::repeat_byte(0x11);
+        let log = |address: Address| {
+            let mut data = [0u8; 32];
+            U256::from(5).to_big_endian(&mut data);
+            Log {
+                address,
+                topics: vec![events::event_topic(events::DEPOSIT_EVENT_SIGNATURE), H256::from(Address::repeat_byte(0x01))],
+                data: Bytes(data.to_vec()),
+                block_hash: None,
+                block_number: Some(7u64.into()),
+                transaction_hash: Some(H256::repeat_byte(0x02)),
+                transaction_index: None,
+                log_index: None,
+                transaction_log_index: None,
+                log_type: None,
+                removed: None,
+            }
+        };
+        let receipt = TransactionReceipt {
+            block_number: Some(7u64.into()),
+            status: Some(1u64.into()),
+            gas_used: Some(21_000u64.into()),
+            effective_gas_price: Some(2u64.into()),
+            logs: vec![log(pool), log(Address::repeat_byte(0x22))],
+            ..TransactionReceipt::default()
+        };
+
+        let confirmed = ConfirmedReceipt::from_mined(receipt, pool, &EventSignatures::default()).unwrap();
+        assert!(confirmed.status);
+        assert_eq!(confirmed.block_number, 7);
+        assert_eq!(confirmed.fee().unwrap(), U256::from(42_000));
+        assert!(matches!(confirmed.events.as_slice(), [FarmEvent::Deposit(event)] if event.amount == U256::from(5)));
+
+        let pending = TransactionReceipt { block_number: None, ..confirmed.raw };
+        assert!(ConfirmedReceipt::from_mined(pending, pool, &EventSignatures::default()).is_none());
+    }
+}
@@ -0,0 +1,105 @@
+use web3::{
+    ethabi::{ParamType, Token},
+    types::{Address, H256, U256},
+    Transport,
+};
+
+use crate::{GasConfig, YieldFarmingClient, YieldFarmingError};
+
+/// View functions that report a referrer's accumulated earnings, in order of preference
+const REFERRAL_FUNCTIONS: [&str; 2] = ["referralRewards", "referralEarnings"];
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Deposit through the pool's `deposit(uint256,address)` overload, crediting `referrer`.
+    ///
+    /// Fails with `SelfReferral` if `referrer` is `account`, and with
+    /// `UnknownFunction` if the pool has no referral overload.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(%amount, ?referrer, ?account), err))]
+    pub async fn deposit_with_referrer(&self, amount: U256, referrer: Address, account: Address) -> Result<H256, YieldFarmingError> {
+        self.deposit_with_referrer_with_gas(amount, referrer, account, &GasConfig::default()).await
+    }
+
+    /// Deposit crediting `referrer` with explicit gas settings, checking pause state if `gas` asks to
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(%amount, ?referrer, ?account), err))]
+    pub async fn deposit_with_referrer_with_gas(
+        &self,
+        amount: U256,
+        referrer: Address,
+        account: Address,
+        gas: &GasConfig,
+    ) -> Result<H256, YieldFarmingError> {
+        if referrer == account {
+            return Err(YieldFarmingError::SelfReferral(account));
+        }
+        let function = self.contract.abi()
+            .functions_by_name("deposit")?
+            .iter()
+            .find(|function| function.inputs.iter().map(|input| &input.kind).eq(&[ParamType::Uint(256), ParamType::Address]))
+            .ok_or_else(|| YieldFarmingError::UnknownFunction("deposit(uint256,address)".to_string()))?;
+        let data = function.encode_input(&[Token::Uint(amount), Token::Address(referrer)])?;
+
+        self.ensure_not_paused(gas).await?;
+        self.deposit_limits().await?.check(amount)?;
+        self.ensure_capacity(amount).await?;
+        let tx_hash = self.send_calldata(data, account, gas.to_options()).await?;
+        self.enforce_deadline(tx_hash, gas.valid_until).await
+    }
+
+    /// Get the referral earnings accumulated by `account`, or `None` if the pool doesn't track them.
+    ///
+    /// Reads `referralRewards(address)` or `referralEarnings(address)`.
+    pub async fn referral_rewards(&self, account: Address) -> Result<Option<U256>, YieldFarmingError> {
+        for function in REFERRAL_FUNCTIONS {
+            if let Some(rewards) = self.optional_view(function, (account,)).await? {
+                return Ok(Some(rewards));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTransport;
+
+    const REFERRAL_ABI: &[u8] = br#"[
+        {"type":"function","name":"deposit","stateMutability":"nonpayable","inputs":[{"name":"amount","type":"uint256"}],"outputs":[]},
+        {"type":"function","name":"deposit","stateMutability":"nonpayable","inputs":[{"name":"amount","type":"uint256"},{"name":"referrer","type":"address"}],"outputs":[]}
+    ]"#;
+
+    #[tokio::test]
+    async fn test_deposit_with_referrer_uses_overload() {
+        let transport = MockTransport::new();
+        transport.set_response("eth_sendTransaction", serde_json::to_value(H256::repeat_byte(0xaa)).unwrap());
+        let (referrer, account) = (Address::repeat_byte(0x01), Address::repeat_byte(0x02));
+
+        let client = YieldFarmingClient::from_transport(transport.clone(), Address::zero(), REFERRAL_ABI).unwrap();
+        assert!(matches!(
+            client.deposit_with_referrer(5.into(), account, account).await,
+            Err(YieldFarmingError::SelfReferral(_))
+        ));
+        client.deposit_with_referrer(5.into(), referrer, account).await.unwrap();
+
+        let sends: Vec<_> = transport.requests().into_iter().filter(|(method, _)| method == "eth_sendTransaction").collect();
+        let selector = web3::signing::keccak256(b"deposit(uint256,address)");
+        let data = sends[0].1[0]["data"].as_str().unwrap().to_string();
+        assert!(data.starts_with(&format!("0x{}", hex::encode(&selector[..4]))));
+        assert_eq!(client.referral_rewards(account).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_deposit_with_referrer_rejects_paused_pool() {
+        const ABI: &[u8] = br#"[
+            {"type":"function","name":"deposit","stateMutability":"nonpayable","inputs":[{"name":"amount","type":"uint256"},{"name":"referrer","type":"address"}],"outputs":[]},
+            {"type":"function","name":"paused","stateMutability":"view","inputs":[],"outputs":[{"name":"","type":"bool"}]}
+        ]"#;
+        let transport = MockTransport::new();
+        transport.push_call_result(&[Token::Bool(true)]);
+        let client = YieldFarmingClient::from_transport(transport.clone(), Address::zero(), ABI).unwrap();
+
+        let gas = GasConfig { check_paused: true, ..GasConfig::default() };
+        let result = client.deposit_with_referrer_with_gas(5.into(), Address::repeat_byte(0x01), Address::repeat_byte(0x02), &gas).await;
+        assert!(matches!(result, Err(YieldFarmingError::PoolPaused)));
+    }
+}
@@ -0,0 +1,411 @@
+use futures::{stream, StreamExt, TryStreamExt};
+use std::time::Duration;
+use serde::Serialize;
+use web3::{
+    api::Eth,
+    contract::{
+        tokens::{Detokenize, Tokenize},
+        Contract, Options,
+    },
+    ethabi::Contract as ContractABI,
+    transports::Http,
+    types::{Address, H256, U256},
+    Transport, Web3,
+};
+
+use crate::apy::{apy_percentage, SECONDS_PER_YEAR};
+use crate::{erc20, fixed, retry, seconds_to_blocks, snapshot, units, PriceOracle, RetryPolicy, YieldFarmingError, MAINNET_BLOCK_TIME};
+
+/// Maximum number of pool queries or withdrawals in flight at once in `exit_all` and `all_pools`
+const POOL_CONCURRENCY: usize = 4;
+
+/// Configuration of a single pool in a MasterChef-style contract
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PoolInfo {
+    pub lp_token: Address,
+    #[serde(serialize_with = "snapshot::serialize_u256")]
+    pub alloc_point: U256,
+    #[serde(serialize_with = "snapshot::serialize_u256")]
+    pub last_reward_block: U256,
+    #[serde(serialize_with = "snapshot::serialize_u256")]
+    pub acc_reward_per_share: U256,
+    /// Rewards emitted to this pool per block, when the master exposes `rewardPerBlock()` and
+    /// `totalAllocPoint()`; only filled in by `all_pools`
+    #[serde(serialize_with = "snapshot::serialize_opt_u256")]
+    pub reward_per_block: Option<U256>,
+    /// Simple yearly rewards over the pool's staked LP value, as a percentage; only filled in by
+    /// `all_pools_with_apy`, and only when `reward_per_block` is known
+    pub apy: Option<f64>,
+}
+
+/// Scale of `accRewardPerShare` in SushiSwap-style MasterChef contracts
+pub const ACC_REWARD_PRECISION: u64 = 1_000_000_000_000;
+
+/// Raw reward accounting of one user in one pool, for checking the contract's pending value
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RewardDebug {
+    #[serde(serialize_with = "snapshot::serialize_u256")]
+    pub acc_reward_per_share: U256,
+    #[serde(serialize_with = "snapshot::serialize_u256")]
+    pub last_reward_block: U256,
+    #[serde(serialize_with = "snapshot::serialize_u256")]
+    pub amount: U256,
+    #[serde(serialize_with = "snapshot::serialize_u256")]
+    pub reward_debt: U256,
+    /// `amount * accRewardPerShare / ACC_REWARD_PRECISION - rewardDebt` from the stored accumulator,
+    /// so it excludes rewards accrued since `last_reward_block`
+    #[serde(serialize_with = "snapshot::serialize_u256")]
+    pub computed_pending: U256,
+    /// What the contract's `pendingRewards` view reports
+    #[serde(serialize_with = "snapshot::serialize_u256")]
+    pub contract_pending: U256,
+}
+
+/// Share of the master's per-block emission going to a pool with `alloc_point`
+fn pool_reward_per_block(reward_per_block: U256, alloc_point: U256, total_alloc_point: U256) -> Result<U256, YieldFarmingError> {
+    fixed::checked_mul_div(reward_per_block, alloc_point, total_alloc_point)
+        .ok_or(YieldFarmingError::ArithmeticOverflow("pool reward per block"))
+}
+
+/// Rewards emitted to a pool over a 365-day year at `reward_per_block` and `block_time` per block
+fn yearly_pool_rewards(reward_per_block: U256, block_time: Duration) -> Result<U256, YieldFarmingError> {
+    reward_per_block
+        .checked_mul(seconds_to_blocks(SECONDS_PER_YEAR, block_time).into())
+        .ok_or(YieldFarmingError::ArithmeticOverflow("yearly pool rewards"))
+}
+
+/// Pending rewards implied by the stored accumulator and a user's reward debt, floored at zero
+fn accrued_pending(amount: U256, acc_reward_per_share: U256, reward_debt: U256) -> Result<U256, YieldFarmingError> {
+    let accrued = fixed::checked_mul_div(amount, acc_reward_per_share, ACC_REWARD_PRECISION.into())
+        .ok_or(YieldFarmingError::ArithmeticOverflow("accrued rewards"))?;
+    Ok(accrued.saturating_sub(reward_debt))
+}
+
+/// Client for MasterChef-style contracts that manage many pools addressed by `pool_id`
+#[derive(Debug)]
+pub struct PoolRegistry<T: Transport = Http> {
+    contract: Contract<T>,
+    eth: Eth<T>,
+    retry_policy: RetryPolicy,
+    request_timeout: Option<Duration>,
+    block_time: Duration,
+}
+
+impl PoolRegistry<Http> {
+    pub fn new(rpc_url: &str, master_address: Address, master_abi: &[u8]) -> Result<Self, YieldFarmingError> {
+        Self::from_transport(Http::new(rpc_url)?, master_address, master_abi)
+    }
+}
+
+impl<T: Transport> PoolRegistry<T> {
+    /// Create a registry on top of an existing transport
+    pub fn from_transport(transport: T, master_address: Address, master_abi: &[u8]) -> Result<Self, YieldFarmingError> {
+        let web3 = Web3::new(transport);
+        let abi = ContractABI::load(master_abi)?;
+        let contract = Contract::new(web3.eth(), master_address, abi);
+
+        Ok(Self {
+            contract,
+            eth: web3.eth(),
+            retry_policy: RetryPolicy::default(),
+            request_timeout: None,
+            block_time: MAINNET_BLOCK_TIME,
+        })
+    }
+
+    /// Set how read-only queries are retried
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Set the timeout applied to every RPC request, failing it with `Timeout`
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the average block time used to annualize `reward_per_block`, `MAINNET_BLOCK_TIME` by default
+    pub fn with_block_time(mut self, block_time: Duration) -> Self {
+        self.block_time = block_time;
+        self
+    }
+
+    /// Address of the master contract
+    pub fn address(&self) -> Address {
+        self.contract.address()
+    }
+
+    /// Deposit tokens into a pool
+    pub async fn deposit(&self, pool_id: U256, amount: U256, account: Address) -> Result<H256, YieldFarmingError> {
+        retry::with_timeout(self.request_timeout, async {
+            Ok(self.contract
+                .call("deposit", (pool_id, amount), account, Options::default())
+                .await?)
+        })
+        .await
+    }
+
+    /// Withdraw tokens from a pool
+    pub async fn withdraw(&self, pool_id: U256, amount: U256, account: Address) -> Result<H256, YieldFarmingError> {
+        retry::with_timeout(self.request_timeout, async {
+            Ok(self.contract
+                .call("withdraw", (pool_id, amount), account, Options::default())
+                .await?)
+        })
+        .await
+    }
+
+    /// Withdraw a user's entire stake from every pool they are staked in, returning the transaction hashes.
+    ///
+    /// Pools where the user has nothing staked are skipped. Stops at the first
+    /// failure, by which point earlier withdrawals may already have been submitted.
+    pub async fn exit_all(&self, account: Address) -> Result<Vec<H256>, YieldFarmingError> {
+        let pool_count = self.pool_length().await?.low_u64();
+        let positions: Vec<(U256, U256)> = stream::iter(0..pool_count)
+            .map(|pool_id| async move {
+                let pool_id = U256::from(pool_id);
+                Ok::<_, YieldFarmingError>((pool_id, self.staked_balance(pool_id, account).await?))
+            })
+            .buffered(POOL_CONCURRENCY)
+            .try_filter(|(_, amount)| futures::future::ready(!amount.is_zero()))
+            .try_collect()
+            .await?;
+
+        stream::iter(positions)
+            .map(|(pool_id, amount)| self.withdraw(pool_id, amount, account))
+            .buffered(POOL_CONCURRENCY)
+            .try_collect()
+            .await
+    }
+
+    /// Get a user's staked balance in a pool from `userInfo`
+    pub async fn staked_balance(&self, pool_id: U256, account: Address) -> Result<U256, YieldFarmingError> {
+        let (amount, _reward_debt): (U256, U256) = self.query_with_retry("userInfo", (pool_id, account)).await?;
+        Ok(amount)
+    }
+
+    /// Get a user's pending rewards in a pool
+    pub async fn pending_rewards(&self, pool_id: U256, account: Address) -> Result<U256, YieldFarmingError> {
+        self.query_with_retry("pendingRewards", (pool_id, account)).await
+    }
+
+    /// Read the reward accumulators behind a user's pending rewards in a pool.
+    ///
+    /// `computed_pending` only matches `contract_pending` once the pool has been
+    /// updated in the current block; before that the difference is the accrual
+    /// since `last_reward_block`, and anything beyond it points to a rounding or
+    /// precision mismatch.
+    pub async fn reward_debug_info(&self, pool_id: U256, account: Address) -> Result<RewardDebug, YieldFarmingError> {
+        let (pool, (amount, reward_debt), contract_pending) = futures::try_join!(
+            self.pool_info(pool_id),
+            self.query_with_retry::<(U256, U256), _>("userInfo", (pool_id, account)),
+            self.pending_rewards(pool_id, account),
+        )?;
+
+        Ok(RewardDebug {
+            acc_reward_per_share: pool.acc_reward_per_share,
+            last_reward_block: pool.last_reward_block,
+            amount,
+            reward_debt,
+            computed_pending: accrued_pending(amount, pool.acc_reward_per_share, reward_debt)?,
+            contract_pending,
+        })
+    }
+
+    /// Get the number of pools
+    pub async fn pool_length(&self) -> Result<U256, YieldFarmingError> {
+        self.query_with_retry("poolLength", ()).await
+    }
+
+    /// Get the configuration of a pool
+    pub async fn pool_info(&self, pool_id: U256) -> Result<PoolInfo, YieldFarmingError> {
+        let (lp_token, alloc_point, last_reward_block, acc_reward_per_share) =
+            self.query_with_retry("poolInfo", (pool_id,)).await?;
+
+        Ok(PoolInfo {
+            lp_token,
+            alloc_point,
+            last_reward_block,
+            acc_reward_per_share,
+            reward_per_block: None,
+            apy: None,
+        })
+    }
+
+    /// Get the configuration of every pool, indexed by pool id.
+    ///
+    /// Also fills in each pool's `reward_per_block` when the master has the views
+    /// to derive it; see `all_pools_with_apy` to turn that into a per-pool APY.
+    pub async fn all_pools(&self) -> Result<Vec<PoolInfo>, YieldFarmingError> {
+        let pool_count = self.pool_length().await?.low_u64();
+        let emission = self.emission().await?;
+
+        stream::iter(0..pool_count)
+            .map(|pool_id| async move {
+                let mut pool = self.pool_info(pool_id.into()).await?;
+                pool.reward_per_block = emission
+                    .map(|(reward_per_block, total_alloc_point)| {
+                        pool_reward_per_block(reward_per_block, pool.alloc_point, total_alloc_point)
+                    })
+                    .transpose()?;
+                Ok(pool)
+            })
+            .buffered(POOL_CONCURRENCY)
+            .try_collect()
+            .await
+    }
+
+    /// Like `all_pools`, also filling in each pool's `apy` when its `reward_per_block` is known.
+    ///
+    /// The APY values a year of the pool's emission, at the registry's block time,
+    /// against the LP tokens the master holds for it. `reward_token` is the token
+    /// the master emits; `oracle` must price it and every pool's LP token.
+    pub async fn all_pools_with_apy(&self, reward_token: Address, oracle: &impl PriceOracle) -> Result<Vec<PoolInfo>, YieldFarmingError> {
+        let pools = self.all_pools().await?;
+        if pools.iter().all(|pool| pool.reward_per_block.is_none()) {
+            return Ok(pools);
+        }
+        let (reward_decimals, reward_price) = futures::try_join!(self.erc20_decimals(reward_token), oracle.price_usd(reward_token))?;
+
+        stream::iter(pools)
+            .map(|mut pool| async move {
+                let Some(reward_per_block) = pool.reward_per_block else {
+                    return Ok(pool);
+                };
+                let (staked, lp_decimals, lp_price) = futures::try_join!(
+                    self.erc20_balance(pool.lp_token, self.address()),
+                    self.erc20_decimals(pool.lp_token),
+                    oracle.price_usd(pool.lp_token),
+                )?;
+                let yearly_rewards = yearly_pool_rewards(reward_per_block, self.block_time)?;
+                pool.apy = Some(apy_percentage(
+                    units::from_wei(yearly_rewards, reward_decimals) * reward_price,
+                    units::from_wei(staked, lp_decimals) * lp_price,
+                ));
+                Ok(pool)
+            })
+            .buffered(POOL_CONCURRENCY)
+            .try_collect()
+            .await
+    }
+
+    async fn erc20_decimals(&self, token: Address) -> Result<u8, YieldFarmingError> {
+        let token = erc20::erc20_contract(self.eth.clone(), token)?;
+        retry::with_timeout(self.request_timeout, async { Ok(token.query("decimals", (), None, Options::default(), None).await?) }).await
+    }
+
+    async fn erc20_balance(&self, token: Address, owner: Address) -> Result<U256, YieldFarmingError> {
+        let token = erc20::erc20_contract(self.eth.clone(), token)?;
+        retry::with_timeout(self.request_timeout, async { Ok(token.query("balanceOf", (owner,), None, Options::default(), None).await?) }).await
+    }
+
+    /// The master's `(rewardPerBlock, totalAllocPoint)`, if its ABI has both
+    async fn emission(&self) -> Result<Option<(U256, U256)>, YieldFarmingError> {
+        let abi = self.contract.abi();
+        if abi.function("rewardPerBlock").is_err() || abi.function("totalAllocPoint").is_err() {
+            return Ok(None);
+        }
+        let emission = futures::try_join!(
+            self.query_with_retry::<U256, _>("rewardPerBlock", ()),
+            self.query_with_retry::<U256, _>("totalAllocPoint", ()),
+        )?;
+        Ok(Some(emission))
+    }
+
+    async fn query_with_retry<R, P>(&self, func: &str, params: P) -> Result<R, YieldFarmingError>
+    where
+        R: Detokenize,
+        P: Tokenize + Clone,
+    {
+        self.retry_policy
+            .retry(|| retry::with_timeout(self.request_timeout, async {
+                let result = self.contract
+                    .query(func, params.clone(), None, Options::default(), None)
+                    .await?;
+                Ok(result)
+            }))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTransport;
+    use web3::ethabi::Token;
+
+    const MASTER_ABI: &[u8] = br#"[
+        {"type":"function","name":"poolLength","stateMutability":"view","inputs":[],"outputs":[{"name":"","type":"uint256"}]},
+        {"type":"function","name":"userInfo","stateMutability":"view","inputs":[{"name":"pid","type":"uint256"},{"name":"user","type":"address"}],"outputs":[{"name":"amount","type":"uint256"},{"name":"rewardDebt","type":"uint256"}]},
+        {"type":"function","name":"withdraw","stateMutability":"nonpayable","inputs":[{"name":"pid","type":"uint256"},{"name":"amount","type":"uint256"}],"outputs":[]}
+    ]"#;
+
+    #[test]
+    fn test_accrued_pending() {
+        // 2 tokens staked at 1.5 rewards per share, 1 already paid out
+        let acc_reward_per_share = U256::from(3 * ACC_REWARD_PRECISION / 2);
+        assert_eq!(accrued_pending(2.into(), acc_reward_per_share, 1.into()).unwrap(), U256::from(2));
+        assert_eq!(accrued_pending(2.into(), acc_reward_per_share, 10.into()).unwrap(), U256::zero());
+        assert!(matches!(accrued_pending(U256::MAX, U256::MAX, 0.into()), Err(YieldFarmingError::ArithmeticOverflow(_))));
+    }
+
+    struct FlatOracle;
+
+    impl PriceOracle for FlatOracle {
+        async fn price_usd(&self, _token: Address) -> Result<f64, YieldFarmingError> {
+            Ok(1.0)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_all_pools_with_apy() {
+        const EMISSION_ABI: &[u8] = br#"[
+            {"type":"function","name":"poolLength","stateMutability":"view","inputs":[],"outputs":[{"name":"","type":"uint256"}]},
+            {"type":"function","name":"poolInfo","stateMutability":"view","inputs":[{"name":"pid","type":"uint256"}],"outputs":[{"name":"lpToken","type":"address"},{"name":"allocPoint","type":"uint256"},{"name":"lastRewardBlock","type":"uint256"},{"name":"accRewardPerShare","type":"uint256"}]},
+            {"type":"function","name":"rewardPerBlock","stateMutability":"view","inputs":[],"outputs":[{"name":"","type":"uint256"}]},
+            {"type":"function","name":"totalAllocPoint","stateMutability":"view","inputs":[],"outputs":[{"name":"","type":"uint256"}]}
+        ]"#;
+        let one = U256::exp10(18);
+        let blocks_per_year = seconds_to_blocks(SECONDS_PER_YEAR, MAINNET_BLOCK_TIME);
+        let transport = MockTransport::new();
+        transport.push_call_result(&[Token::Uint(1.into())]);
+        // Half of a 2-token emission goes to the only pool
+        transport.push_call_result(&[Token::Uint(one * 2)]);
+        transport.push_call_result(&[Token::Uint(200.into())]);
+        transport.push_call_result(&[Token::Address(Address::repeat_byte(0x33)), Token::Uint(100.into()), Token::Uint(0.into()), Token::Uint(0.into())]);
+        transport.push_call_result(&[Token::Uint(18.into())]);
+        // Staked value equal to a year of rewards, so 100%
+        transport.push_call_result(&[Token::Uint(one * blocks_per_year)]);
+        transport.push_call_result(&[Token::Uint(18.into())]);
+
+        let registry = PoolRegistry::from_transport(transport.clone(), Address::repeat_byte(0x11), EMISSION_ABI).unwrap();
+        let pools = registry.all_pools_with_apy(Address::repeat_byte(0x44), &FlatOracle).await.unwrap();
+
+        assert_eq!(pools[0].reward_per_block, Some(one));
+        assert!((pools[0].apy.unwrap() - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_pool_reward_per_block() {
+        assert_eq!(pool_reward_per_block(100.into(), 30.into(), 120.into()).unwrap(), U256::from(25));
+        assert_eq!(pool_reward_per_block(100.into(), 30.into(), U256::zero()).unwrap(), U256::zero());
+    }
+
+    #[tokio::test]
+    async fn test_exit_all_skips_empty_pools() {
+        let transport = MockTransport::new();
+        transport.push_call_result(&[Token::Uint(3.into())]);
+        for amount in [0u64, 500, 0] {
+            transport.push_call_result(&[Token::Uint(amount.into()), Token::Uint(0.into())]);
+        }
+        transport.set_response("eth_sendTransaction", serde_json::to_value(H256::repeat_byte(0xaa)).unwrap());
+
+        let registry = PoolRegistry::from_transport(transport.clone(), Address::repeat_byte(0x11), MASTER_ABI).unwrap();
+        let hashes = registry.exit_all(Address::repeat_byte(0x22)).await.unwrap();
+
+        assert_eq!(hashes, vec![H256::repeat_byte(0xaa)]);
+        let sends: Vec<_> = transport.requests().into_iter().filter(|(method, _)| method == "eth_sendTransaction").collect();
+        assert_eq!(sends.len(), 1);
+    }
+}
@@ -0,0 +1,170 @@
+use std::future::Future;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::YieldFarmingError;
+
+/// Exponential backoff settings for retrying read-only RPC calls
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt; zero disables retrying
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled on every subsequent one
+    pub base_delay: Duration,
+    /// Upper bound on the delay between attempts
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Backoff before retry number `attempt` (starting at zero), with jitter in the upper half
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let exponential = self.base_delay.saturating_mul(2u32.saturating_pow(attempt));
+        let capped = exponential.min(self.max_delay);
+
+        let half = capped / 2;
+        let jitter_nanos = half.as_nanos() as u64;
+        if jitter_nanos == 0 {
+            return capped;
+        }
+        half + Duration::from_nanos(pseudo_random() % jitter_nanos)
+    }
+
+    /// Run `op`, retrying transient transport failures according to the policy.
+    ///
+    /// Only use this for read-only calls: a transport error after a write was
+    /// sent doesn't mean the write wasn't applied.
+    pub(crate) async fn retry<F, Fut, R>(&self, mut op: F) -> Result<R, YieldFarmingError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<R, YieldFarmingError>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match op().await {
+                Err(err) if attempt < self.max_retries && is_transient(&err) => {
+                    tokio::time::sleep(self.delay_for(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Await `call`, failing with `Timeout` if it runs longer than `timeout`
+pub(crate) async fn with_timeout<R>(
+    timeout: Option<Duration>,
+    call: impl Future<Output = Result<R, YieldFarmingError>>,
+) -> Result<R, YieldFarmingError> {
+    match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, call)
+            .await
+            .unwrap_or(Err(YieldFarmingError::Timeout)),
+        None => call.await,
+    }
+}
+
+/// Whether an error is a connection-level failure worth retrying, as opposed to a revert
+pub(crate) fn is_transient(err: &YieldFarmingError) -> bool {
+    match err {
+        YieldFarmingError::Transport(err) => is_transient_web3(err),
+        YieldFarmingError::Contract(web3::contract::Error::Api(err)) => is_transient_web3(err),
+        YieldFarmingError::Timeout => true,
+        _ => false,
+    }
+}
+
+pub(crate) fn is_transient_web3(err: &web3::Error) -> bool {
+    match err {
+        web3::Error::Unreachable | web3::Error::Transport(_) | web3::Error::Io(_) => true,
+        // Provider rate limiting surfaced as a JSON-RPC error
+        web3::Error::Rpc(rpc) => matches!(rpc.code.code(), 429 | -32005),
+        _ => false,
+    }
+}
+
+// Jitter only needs to spread clients apart, not be unpredictable
+fn pseudo_random() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.subsec_nanos())
+        .unwrap_or_default();
+    (nanos as u64).wrapping_mul(6364136223846793005).rotate_left(17)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_with_timeout() {
+        let slow = async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        };
+        let result = with_timeout(Some(Duration::from_millis(10)), slow).await;
+        assert!(matches!(result, Err(YieldFarmingError::Timeout)));
+        assert_eq!(with_timeout(None, async { Ok(1) }).await.unwrap(), 1);
+    }
+
+    #[test]
+    fn test_delay_is_exponential_and_capped() {
+        let policy = RetryPolicy {
+            max_retries: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(500),
+        };
+
+        let second = policy.delay_for(1);
+        assert!(second >= Duration::from_millis(100) && second <= Duration::from_millis(200));
+
+        let capped = policy.delay_for(10);
+        assert!(capped >= Duration::from_millis(250) && capped <= Duration::from_millis(500));
+    }
+
+    #[tokio::test]
+    async fn test_retries_only_transient_errors() {
+        let policy = RetryPolicy {
+            max_retries: 2,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(1),
+        };
+
+        let mut calls = 0;
+        let result: Result<(), _> = policy
+            .retry(|| {
+                calls += 1;
+                async { Err(YieldFarmingError::Transport(web3::Error::Unreachable)) }
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+
+        let mut calls = 0;
+        let result: Result<(), _> = policy
+            .retry(|| {
+                calls += 1;
+                async { Err(YieldFarmingError::Reverted { reason: None }) }
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+}
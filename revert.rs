@@ -0,0 +1,175 @@
+//! Decoding of EVM revert data returned by nodes for failed calls.
+
+use web3::{
+    ethabi::{self, Contract as ContractABI, ParamType, Token},
+    Transport,
+};
+
+use crate::{YieldFarmingClient, YieldFarmingError};
+
+/// Selector of `Error(string)`, used by `require(cond, "reason")` and `revert("reason")`
+pub const ERROR_STRING_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// Selector of `Panic(uint256)`, used for assertion failures and arithmetic errors
+pub const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// Decode a human-readable reason from ABI-encoded revert data
+pub fn decode_revert_reason(data: &[u8]) -> Option<String> {
+    if data.len() < 4 {
+        return None;
+    }
+    let (selector, payload) = data.split_at(4);
+
+    if selector == ERROR_STRING_SELECTOR {
+        match ethabi::decode(&[ParamType::String], payload).ok()?.pop()? {
+            Token::String(reason) => Some(reason),
+            _ => None,
+        }
+    } else if selector == PANIC_SELECTOR {
+        match ethabi::decode(&[ParamType::Uint(256)], payload).ok()?.pop()? {
+            Token::Uint(code) => Some(format!("panic code {:#x}", code)),
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
+
+/// Decode revert data as one of the custom errors declared in `abi`, returning its name and arguments
+pub fn decode_custom_error(abi: &ContractABI, data: &[u8]) -> Option<(String, Vec<Token>)> {
+    if data.len() < 4 {
+        return None;
+    }
+    let (selector, payload) = data.split_at(4);
+
+    let error = abi.errors().find(|error| error.signature()[..4] == *selector)?;
+    let params = error.decode(payload).ok()?;
+    Some((error.name.clone(), params))
+}
+
+/// Render decoded error arguments for display, with integers in decimal and addresses in hex
+pub(crate) fn format_params(params: &[Token]) -> String {
+    params
+        .iter()
+        .map(|param| match param {
+            Token::Uint(value) | Token::Int(value) => value.to_string(),
+            Token::Address(address) => format!("{:?}", address),
+            other => other.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The error for failed call `data`: a custom error from `abi` if it matches one, otherwise `Reverted`
+pub(crate) fn revert_error(abi: &ContractABI, data: &[u8]) -> YieldFarmingError {
+    match decode_custom_error(abi, data) {
+        Some((name, params)) => YieldFarmingError::CustomError { name, params },
+        None => YieldFarmingError::Reverted { reason: decode_revert_reason(data) },
+    }
+}
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Convert a node error from calling the pool, decoding custom errors declared in its ABI
+    pub(crate) fn rpc_error(&self, err: web3::Error) -> YieldFarmingError {
+        if let web3::Error::Rpc(rpc) = &err {
+            let custom = rpc.data.as_ref().and_then(revert_data).and_then(|data| decode_custom_error(self.contract.abi(), &data));
+            if let Some((name, params)) = custom {
+                return YieldFarmingError::CustomError { name, params };
+            }
+        }
+        YieldFarmingError::from_rpc_call(err)
+    }
+
+    /// Like `rpc_error`, for errors returned through `Contract`
+    pub(crate) fn contract_error(&self, err: web3::contract::Error) -> YieldFarmingError {
+        match err {
+            web3::contract::Error::Api(err) => self.rpc_error(err),
+            other => YieldFarmingError::from_contract_call(other),
+        }
+    }
+}
+
+/// If a node error describes a revert, return its reason (which may itself be unknown)
+pub(crate) fn revert_reason(err: &web3::Error) -> Option<Option<String>> {
+    let rpc = match err {
+        web3::Error::Rpc(rpc) => rpc,
+        _ => return None,
+    };
+
+    let data = rpc.data.as_ref().and_then(revert_data);
+    if data.is_none() && !rpc.message.contains("revert") {
+        return None;
+    }
+
+    let reason = data
+        .as_deref()
+        .and_then(decode_revert_reason)
+        .or_else(|| {
+            rpc.message
+                .split_once("execution reverted:")
+                .map(|(_, reason)| reason.trim().to_string())
+                .filter(|reason| !reason.is_empty())
+        });
+    Some(reason)
+}
+
+/// Extract hex revert data from the `data` field of a JSON-RPC error.
+///
+/// Geth returns it as a plain hex string; some dev nodes nest it in an object.
+pub(crate) fn revert_data(value: &serde_json::Value) -> Option<Vec<u8>> {
+    match value {
+        serde_json::Value::String(hex_data) => {
+            let bytes = hex::decode(hex_data.strip_prefix("0x")?).ok()?;
+            Some(bytes).filter(|bytes| bytes.len() >= 4)
+        }
+        serde_json::Value::Object(object) => object.get("data").and_then(revert_data),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_string(reason: &str) -> Vec<u8> {
+        let mut data = ERROR_STRING_SELECTOR.to_vec();
+        data.extend(ethabi::encode(&[Token::String(reason.into())]));
+        data
+    }
+
+    #[test]
+    fn test_decode_error_string() {
+        assert_eq!(decode_revert_reason(&error_string("insufficient balance")).as_deref(), Some("insufficient balance"));
+    }
+
+    #[test]
+    fn test_decode_panic() {
+        let mut data = PANIC_SELECTOR.to_vec();
+        data.extend(ethabi::encode(&[Token::Uint(0x11.into())]));
+        assert_eq!(decode_revert_reason(&data).as_deref(), Some("panic code 0x11"));
+    }
+
+    #[test]
+    fn test_decode_custom_error() {
+        let abi = ContractABI::load(
+            br#"[{"type":"error","name":"InsufficientBalance","inputs":[{"name":"available","type":"uint256"},{"name":"required","type":"uint256"}]}]"#
+                .as_slice(),
+        )
+        .unwrap();
+        let error = abi.error("InsufficientBalance").unwrap();
+        let data = error.encode(&[Token::Uint(5.into()), Token::Uint(10.into())]).unwrap();
+
+        let err = revert_error(&abi, &data);
+        assert!(matches!(&err, YieldFarmingError::CustomError { name, params } if name == "InsufficientBalance" && params.len() == 2));
+        assert_eq!(err.to_string(), "execution reverted: InsufficientBalance(5, 10)");
+        assert!(matches!(revert_error(&abi, &error_string("paused")), YieldFarmingError::Reverted { reason: Some(_) }));
+    }
+
+    #[test]
+    fn test_revert_data_formats() {
+        let hex_data = format!("0x{}", hex::encode(error_string("paused")));
+        assert!(revert_data(&serde_json::json!(hex_data)).is_some());
+        assert!(revert_data(&serde_json::json!({ "data": hex_data })).is_some());
+        assert!(revert_data(&serde_json::json!("Reverted")).is_none());
+    }
+}
@@ -0,0 +1,212 @@
+use web3::{
+    ethabi::{Contract as ContractABI, Function, ParamType, Token},
+    types::{Address, BlockId, BlockNumber, Bytes, CallRequest, U256},
+    Transport,
+};
+
+use crate::cache::{ViewCache, REWARD_PAID_SUM};
+use crate::events::UserAmountEvent;
+use crate::{retry, trace, YieldFarmingClient, YieldFarmingError};
+
+/// View functions that report a user's pending rewards, in order of preference
+const PENDING_FUNCTIONS: [&str; 2] = ["earned", "pendingRewards"];
+
+/// View functions that report lifetime rewards paid, in order of preference
+const DISTRIBUTED_FUNCTIONS: [&str; 2] = ["totalRewardsPaid", "rewardDistributed"];
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Get `account`'s pending rewards in every reward token the pool emits, paired with the token addresses.
+    ///
+    /// Understands, in order:
+    /// - `earned`/`pendingRewards(user)` returning `(address[] tokens, uint256[] amounts)`
+    /// - a `rewardTokens()` list with `earned`/`pendingRewards(user, token)` per token
+    /// - a `rewardTokens()` list with `earned`/`pendingRewards(user)` returning `uint256[]` in the same order
+    ///
+    /// Single-reward pools are returned as one `(reward_token, pending)` pair.
+    pub async fn pending_rewards_multi(&self, account: Address) -> Result<Vec<(Address, U256)>, YieldFarmingError> {
+        let abi = self.contract.abi();
+        let user = [Token::Address(account)];
+
+        if let Some(function) = find_pending(abi, &[ParamType::Address], |outputs| {
+            matches!(outputs, [ParamType::Array(tokens), ParamType::Array(amounts)]
+                if **tokens == ParamType::Address && **amounts == ParamType::Uint(256))
+        }) {
+            let output = self.call_view(function, &user).await?;
+            return match output.as_slice() {
+                [Token::Array(tokens), Token::Array(amounts)] => pair_rewards(tokens.iter().map(as_address).collect::<Option<_>>(), amounts),
+                _ => Err(multi_reward_error()),
+            };
+        }
+
+        if abi.function("rewardTokens").is_ok() {
+            let tokens: Vec<Address> = self.query_with_retry("rewardTokens", ()).await?;
+
+            if let Some(function) = find_pending(abi, &[ParamType::Address, ParamType::Address], |outputs| {
+                outputs == [ParamType::Uint(256)]
+            }) {
+                let amounts = futures::future::try_join_all(tokens.iter().map(|token| async move {
+                    match self.call_view(function, &[Token::Address(account), Token::Address(*token)]).await?.as_slice() {
+                        [Token::Uint(amount)] => Ok((*token, *amount)),
+                        _ => Err(multi_reward_error()),
+                    }
+                }))
+                .await?;
+                return Ok(amounts);
+            }
+
+            if let Some(function) = find_pending(abi, &[ParamType::Address], |outputs| {
+                matches!(outputs, [ParamType::Array(amounts)] if **amounts == ParamType::Uint(256))
+            }) {
+                return match self.call_view(function, &user).await?.as_slice() {
+                    [Token::Array(amounts)] => pair_rewards(Some(tokens), amounts),
+                    _ => Err(multi_reward_error()),
+                };
+            }
+        }
+
+        let (token, pending) = futures::try_join!(self.reward_token(), self.get_pending_rewards(account))?;
+        Ok(vec![(token, pending)])
+    }
+
+    /// Get the lifetime rewards the pool has paid out, in reward token base units.
+    ///
+    /// Reads `totalRewardsPaid()` or `rewardDistributed()` when the pool has one.
+    /// Otherwise sums every `RewardPaid` event since genesis, which scans the
+    /// pool's whole history in `log_chunk_size` windows and can take many
+    /// requests; that sum is cached for ten minutes.
+    pub async fn total_rewards_distributed(&self) -> Result<U256, YieldFarmingError> {
+        for function in DISTRIBUTED_FUNCTIONS {
+            if let Some(total) = self.optional_view::<U256, _>(function, ()).await? {
+                return Ok(total);
+            }
+        }
+
+        self.view_cache
+            .get_or_fetch(ViewCache::key(self.contract.address(), REWARD_PAID_SUM, ()), || async {
+                trace::warning!("no lifetime rewards view; summing RewardPaid events since genesis");
+                let topic = self.event_signatures.reward_paid_topic();
+                let logs = self
+                    .get_logs_in_range(vec![topic], None, BlockNumber::Earliest, BlockNumber::Latest, self.request_timeout)
+                    .await?;
+                logs.into_iter().try_fold(U256::zero(), |total, log| {
+                    let event = UserAmountEvent::try_from(log)?;
+                    if event.removed {
+                        return Ok(total);
+                    }
+                    total.checked_add(event.amount).ok_or(YieldFarmingError::ArithmeticOverflow("rewards distributed"))
+                })
+            })
+            .await
+    }
+
+    /// Call a specific ABI function, for overloads `Contract::query` can't select
+    pub(crate) async fn call_view(&self, function: &Function, params: &[Token]) -> Result<Vec<Token>, YieldFarmingError> {
+        let request = CallRequest {
+            to: Some(self.contract.address()),
+            data: Some(Bytes(function.encode_input(params)?)),
+            ..CallRequest::default()
+        };
+        let output = self.retry_policy
+            .retry(|| retry::with_timeout(self.request_timeout, async {
+                Ok(self.web3.eth().call(request.clone(), Some(BlockId::Number(BlockNumber::Latest))).await?)
+            }))
+            .await?;
+        Ok(function.decode_output(&output.0)?)
+    }
+}
+
+/// First pending-rewards overload taking `inputs` whose outputs satisfy `outputs`
+fn find_pending<'a>(abi: &'a ContractABI, inputs: &[ParamType], outputs: impl Fn(&[ParamType]) -> bool) -> Option<&'a Function> {
+    PENDING_FUNCTIONS
+        .iter()
+        .filter_map(|name| abi.functions_by_name(name).ok())
+        .flatten()
+        .find(|function| {
+            function.inputs.iter().map(|input| &input.kind).eq(inputs.iter())
+                && outputs(&function.outputs.iter().map(|output| output.kind.clone()).collect::<Vec<_>>())
+        })
+}
+
+/// Zip reward tokens with their amounts, which must line up one-to-one
+fn pair_rewards(tokens: Option<Vec<Address>>, amounts: &[Token]) -> Result<Vec<(Address, U256)>, YieldFarmingError> {
+    let tokens = tokens.ok_or_else(multi_reward_error)?;
+    if tokens.len() != amounts.len() {
+        return Err(multi_reward_error());
+    }
+    tokens
+        .into_iter()
+        .zip(amounts)
+        .map(|(token, amount)| match amount {
+            Token::Uint(amount) => Ok((token, *amount)),
+            _ => Err(multi_reward_error()),
+        })
+        .collect()
+}
+
+fn as_address(token: &Token) -> Option<Address> {
+    match token {
+        Token::Address(address) => Some(*address),
+        _ => None,
+    }
+}
+
+fn multi_reward_error() -> YieldFarmingError {
+    YieldFarmingError::Contract(web3::contract::Error::InvalidOutputType(
+        "reward tokens and pending amounts do not line up".into(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTransport;
+
+    const MULTI_REWARD_ABI: &[u8] = br#"[
+        {"type":"function","name":"rewardTokens","stateMutability":"view","inputs":[],"outputs":[{"name":"","type":"address[]"}]},
+        {"type":"function","name":"earned","stateMutability":"view","inputs":[{"name":"account","type":"address"},{"name":"rewardsToken","type":"address"}],"outputs":[{"name":"","type":"uint256"}]}
+    ]"#;
+
+    #[tokio::test]
+    async fn test_pending_rewards_per_token() {
+        let transport = MockTransport::new();
+        let (first, second) = (Address::repeat_byte(0x01), Address::repeat_byte(0x02));
+        transport.push_call_result(&[Token::Array(vec![Token::Address(first), Token::Address(second)])]);
+        transport.push_call_result(&[Token::Uint(5.into())]);
+        transport.push_call_result(&[Token::Uint(7.into())]);
+
+        let client = YieldFarmingClient::from_transport(transport, Address::zero(), MULTI_REWARD_ABI).unwrap();
+        let rewards = client.pending_rewards_multi(Address::repeat_byte(0x03)).await.unwrap();
+        assert_eq!(rewards, vec![(first, U256::from(5)), (second, U256::from(7))]);
+    }
+
+    #[test]
+    fn test_pair_rewards_length_mismatch() {
+        let amounts = [Token::Uint(1.into())];
+        assert!(pair_rewards(Some(vec![Address::zero(), Address::zero()]), &amounts).is_err());
+        assert_eq!(pair_rewards(Some(vec![Address::zero()]), &amounts).unwrap(), vec![(Address::zero(), U256::one())]);
+    }
+
+    #[tokio::test]
+    async fn test_total_rewards_distributed_sums_events_once() {
+        let transport = MockTransport::new();
+        let log = |amount: u64| {
+            let mut data = [0u8; 32];
+            U256::from(amount).to_big_endian(&mut data);
+            serde_json::json!({
+                "address": Address::zero(),
+                "topics": [crate::events::REWARD_PAID_TOPIC, web3::types::H256::from(Address::repeat_byte(0x01))],
+                "data": Bytes(data.to_vec()),
+                "blockNumber": "0x1",
+                "transactionHash": web3::types::H256::repeat_byte(0x02),
+                "logIndex": "0x0",
+            })
+        };
+        transport.set_response("eth_blockNumber", serde_json::json!("0x10"));
+        transport.set_response("eth_getLogs", serde_json::json!([log(5), log(7)]));
+
+        let client = YieldFarmingClient::from_transport(transport.clone(), Address::zero(), b"[]").unwrap();
+        assert_eq!(client.total_rewards_distributed().await.unwrap(), U256::from(12));
+        assert_eq!(client.total_rewards_distributed().await.unwrap(), U256::from(12));
+        assert_eq!(transport.requests().iter().filter(|(method, _)| method == "eth_getLogs").count(), 1);
+    }
+}
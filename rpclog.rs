@@ -0,0 +1,126 @@
+//! Transport wrapper that logs every raw JSON-RPC exchange when the builder's `debug_rpc` is on.
+
+use futures::future::Future;
+use jsonrpc_core as rpc;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use web3::{api::SubscriptionId, DuplexTransport, RequestId, Transport};
+
+use crate::trace;
+
+/// The client's transport, logging requests and responses at `trace` level while enabled.
+///
+/// Clones share the switch, so it can be flipped after the client is built.
+#[derive(Debug, Clone)]
+pub(crate) struct LoggedTransport<T> {
+    inner: T,
+    enabled: Arc<AtomicBool>,
+}
+
+impl<T> LoggedTransport<T> {
+    /// Wrap `inner` with logging off
+    pub(crate) fn new(inner: T) -> Self {
+        Self {
+            inner,
+            enabled: Arc::default(),
+        }
+    }
+
+    /// Wrap another transport, sharing this one's switch
+    pub(crate) fn wrap<U>(&self, inner: U) -> LoggedTransport<U> {
+        LoggedTransport {
+            inner,
+            enabled: self.enabled.clone(),
+        }
+    }
+
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+}
+
+impl<T: Transport> Transport for LoggedTransport<T> {
+    type Out = Logged<T::Out>;
+
+    fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+        self.inner.prepare(method, params)
+    }
+
+    fn send(&self, id: RequestId, request: rpc::Call) -> Self::Out {
+        let enabled = self.enabled.load(Ordering::Relaxed);
+        if enabled {
+            log_request(id, &request);
+        }
+        Logged {
+            inner: Box::pin(self.inner.send(id, request)),
+            id: enabled.then_some(id),
+        }
+    }
+}
+
+impl<T: DuplexTransport> DuplexTransport for LoggedTransport<T> {
+    type NotificationStream = T::NotificationStream;
+
+    fn subscribe(&self, id: SubscriptionId) -> web3::Result<Self::NotificationStream> {
+        self.inner.subscribe(id)
+    }
+
+    fn unsubscribe(&self, id: SubscriptionId) -> web3::Result<()> {
+        self.inner.unsubscribe(id)
+    }
+}
+
+/// A request's response future, logging the raw response if the request was logged
+pub(crate) struct Logged<F> {
+    inner: Pin<Box<F>>,
+    id: Option<RequestId>,
+}
+
+impl<F: Future<Output = web3::Result<rpc::Value>>> Future for Logged<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let result = futures::ready!(self.inner.as_mut().poll(cx));
+        if let Some(id) = self.id {
+            log_response(id, &result);
+        }
+        Poll::Ready(result)
+    }
+}
+
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+fn log_request(id: RequestId, request: &rpc::Call) {
+    trace::trace!(id, request = %serde_json::to_string(request).unwrap_or_default(), "RPC request");
+}
+
+/// Log the response as sent by the node, so `eth_call` results show their hex before decoding
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+fn log_response(id: RequestId, result: &web3::Result<rpc::Value>) {
+    match result {
+        Ok(response) => {
+            trace::trace!(id, %response, "RPC response");
+        }
+        Err(err) => {
+            trace::trace!(id, error = %err, "RPC request failed");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTransport;
+
+    #[tokio::test]
+    async fn test_logged_transport_passes_responses_through() {
+        let mock = MockTransport::new();
+        mock.set_response("eth_blockNumber", serde_json::json!("0x10"));
+        let transport = LoggedTransport::new(mock);
+        transport.set_enabled(true);
+
+        assert_eq!(transport.execute("eth_blockNumber", vec![]).await.unwrap(), serde_json::json!("0x10"));
+        assert!(transport.wrap(MockTransport::new()).enabled.load(Ordering::Relaxed));
+    }
+}
@@ -0,0 +1,77 @@
+use std::time::Duration;
+use web3::{
+    types::{Address, H256, U256},
+    Transport,
+};
+
+use crate::{retry, trace, PoolSnapshot, YieldFarmingClient, YieldFarmingError};
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Deposit `amount` from `account` once `condition` holds for the pool's state, e.g. an APY
+    /// above a threshold or a base fee below a limit.
+    ///
+    /// Takes a `snapshot` every `poll_interval` and deposits as soon as `condition`
+    /// accepts one. Transient RPC failures are skipped; other errors stop polling.
+    /// Fails with `ConditionNotMet` if the condition hasn't held within `timeout`.
+    pub async fn deposit_when(
+        &self,
+        amount: U256,
+        account: Address,
+        condition: impl Fn(&PoolSnapshot) -> bool,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<H256, YieldFarmingError> {
+        let wait = async {
+            loop {
+                match self.snapshot(account).await {
+                    Ok(snapshot) if condition(&snapshot) => return Ok(()),
+                    Ok(_) => {}
+                    Err(err) if retry::is_transient(&err) => {
+                        trace::debug!(error = %err, "skipping failed snapshot while waiting to deposit");
+                    }
+                    Err(err) => return Err(err),
+                }
+                tokio::time::sleep(poll_interval).await;
+            }
+        };
+        tokio::time::timeout(timeout, wait)
+            .await
+            .map_err(|_| YieldFarmingError::ConditionNotMet(timeout))??;
+
+        self.deposit(amount, account).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTransport;
+    use web3::{ethabi::Token, types::Block};
+
+    const POOL_ABI: &[u8] = br#"[
+        {"type":"function","name":"balanceOf","stateMutability":"view","inputs":[{"name":"account","type":"address"}],"outputs":[{"name":"","type":"uint256"}]},
+        {"type":"function","name":"pendingRewards","stateMutability":"view","inputs":[{"name":"user","type":"address"}],"outputs":[{"name":"","type":"uint256"}]},
+        {"type":"function","name":"totalValueLocked","stateMutability":"view","inputs":[],"outputs":[{"name":"","type":"uint256"}]}
+    ]"#;
+
+    #[tokio::test]
+    async fn test_deposit_when_times_out() {
+        let transport = MockTransport::new();
+        let block = Block::<H256> {
+            number: Some(1u64.into()),
+            base_fee_per_gas: Some(100u64.into()),
+            ..Block::default()
+        };
+        transport.set_response("eth_getBlockByNumber", serde_json::to_value(&block).unwrap());
+        transport.set_response("eth_call", serde_json::to_value(web3::types::Bytes(web3::ethabi::encode(&[Token::Uint(1.into())]))).unwrap());
+
+        let client = YieldFarmingClient::from_transport(transport.clone(), Address::zero(), POOL_ABI).unwrap();
+        let cheap_gas = |snapshot: &PoolSnapshot| snapshot.base_fee.is_some_and(|fee| fee < U256::from(50));
+        let result = client
+            .deposit_when(1.into(), Address::repeat_byte(0x01), cheap_gas, Duration::from_millis(5), Duration::from_millis(50))
+            .await;
+
+        assert!(matches!(result, Err(YieldFarmingError::ConditionNotMet(_))));
+        assert!(transport.requests().iter().all(|(method, _)| method != "eth_sendTransaction"));
+    }
+}
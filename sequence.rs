@@ -0,0 +1,141 @@
+use web3::{
+    contract::tokens::Tokenize,
+    types::{Address, BlockNumber, TransactionRequest, H256, U256},
+    Transport,
+};
+
+use crate::{erc20, retry, telemetry, trace, YieldFarmingClient, YieldFarmingError, DEFAULT_MAX_POLL_ATTEMPTS, DEFAULT_POLL_INTERVAL};
+
+/// One write in a batch submitted with `submit_sequential`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreparedCall {
+    to: Address,
+    data: Vec<u8>,
+    value: Option<U256>,
+    gas: Option<U256>,
+}
+
+impl PreparedCall {
+    /// Call `to` with already ABI-encoded `data`
+    pub fn new(to: Address, data: Vec<u8>) -> Self {
+        Self {
+            to,
+            data,
+            value: None,
+            gas: None,
+        }
+    }
+
+    /// ERC20 `approve(spender, amount)` on `token`
+    pub fn approve(token: Address, spender: Address, amount: U256) -> Result<Self, YieldFarmingError> {
+        let abi = erc20::erc20_abi()?;
+        let data = abi.function("approve")?.encode_input(&(spender, amount).into_tokens())?;
+        Ok(Self::new(token, data))
+    }
+
+    /// Attach native currency to the call
+    pub fn value(mut self, value: U256) -> Self {
+        self.value = Some(value);
+        self
+    }
+
+    /// Use a fixed gas limit instead of the node's estimate
+    pub fn gas(mut self, gas: U256) -> Self {
+        self.gas = Some(gas);
+        self
+    }
+}
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Prepare a call to `func` on the farming contract for `submit_sequential`
+    pub fn prepare(&self, func: &str, params: impl Tokenize) -> Result<PreparedCall, YieldFarmingError> {
+        let data = self.contract.abi().function(func)?.encode_input(&params.into_tokens())?;
+        Ok(PreparedCall::new(self.contract.address(), data))
+    }
+
+    /// Submit `calls` from `account` in order, returning their transaction hashes.
+    ///
+    /// Calls get consecutive nonces starting at the account's pending nonce,
+    /// and each is sent only once the previous one is mined, so a step such as
+    /// a deposit never lands before the approval it depends on. Stops with
+    /// `TransactionReverted` if a step reverts.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(?account, calls = calls.len()), err))]
+    pub async fn submit_sequential(&self, calls: Vec<PreparedCall>, account: Address) -> Result<Vec<H256>, YieldFarmingError> {
+        let first_nonce = retry::with_timeout(self.request_timeout, async {
+            Ok(self.web3.eth().transaction_count(account, Some(BlockNumber::Pending)).await?)
+        })
+        .await?;
+
+        let mut hashes = Vec::with_capacity(calls.len());
+        for (nonce, call) in (0u64..).map(|offset| first_nonce + offset).zip(calls) {
+            let request = TransactionRequest {
+                from: account,
+                to: Some(call.to),
+                gas: call.gas,
+                value: call.value,
+                data: Some(call.data.into()),
+                nonce: Some(nonce),
+                ..TransactionRequest::default()
+            };
+
+            trace::debug!(method = "eth_sendTransaction", %nonce, "submitting sequential call");
+            let tx_hash = telemetry::rpc("eth_sendTransaction", retry::with_timeout(self.request_timeout, async {
+                self.web3.eth()
+                    .send_transaction(request)
+                    .await
+                    .map_err(|err| self.rpc_error(err))
+            }))
+            .await?;
+            telemetry::tx_submitted();
+
+            let receipt = self
+                .wait_for_confirmation(tx_hash, 0, DEFAULT_POLL_INTERVAL, DEFAULT_MAX_POLL_ATTEMPTS)
+                .await?;
+            if !receipt.status {
+                telemetry::tx_reverted();
+                return Err(YieldFarmingError::TransactionReverted(tx_hash));
+            }
+            hashes.push(tx_hash);
+        }
+
+        Ok(hashes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTransport;
+    use web3::types::TransactionReceipt;
+
+    const POOL_ABI: &[u8] = br#"[
+        {"type":"function","name":"deposit","stateMutability":"nonpayable","inputs":[{"name":"amount","type":"uint256"}],"outputs":[]}
+    ]"#;
+
+    #[tokio::test]
+    async fn test_submit_sequential_uses_consecutive_nonces() {
+        let transport = MockTransport::new();
+        let receipt = TransactionReceipt {
+            block_number: Some(1u64.into()),
+            status: Some(1u64.into()),
+            ..TransactionReceipt::default()
+        };
+        transport.set_response("eth_getTransactionCount", serde_json::json!("0x7"));
+        transport.set_response("eth_sendTransaction", serde_json::to_value(H256::repeat_byte(0xaa)).unwrap());
+        transport.set_response("eth_getTransactionReceipt", serde_json::to_value(&receipt).unwrap());
+        transport.set_response("eth_blockNumber", serde_json::json!("0x1"));
+
+        let client = YieldFarmingClient::from_transport(transport.clone(), Address::repeat_byte(0x11), POOL_ABI).unwrap();
+        let calls = vec![
+            PreparedCall::approve(Address::repeat_byte(0x22), client.contract.address(), 100.into()).unwrap(),
+            client.prepare("deposit", (U256::from(100),)).unwrap(),
+        ];
+        let hashes = client.submit_sequential(calls, Address::repeat_byte(0x33)).await.unwrap();
+        assert_eq!(hashes.len(), 2);
+
+        let sends: Vec<_> = transport.requests().into_iter().filter(|(method, _)| method == "eth_sendTransaction").collect();
+        assert_eq!(sends[0].1[0]["nonce"], "0x7");
+        assert_eq!(sends[0].1[0]["to"], serde_json::to_value(Address::repeat_byte(0x22)).unwrap());
+        assert_eq!(sends[1].1[0]["nonce"], "0x8");
+    }
+}
@@ -0,0 +1,203 @@
+use std::fmt;
+use web3::{
+    contract::{tokens::Tokenize, Options},
+    signing::{Key, SecretKey, SecretKeyRef},
+    types::{Address, Bytes, TransactionParameters, H256, U256},
+    Transport,
+};
+
+use crate::{retry, telemetry, trace, GasConfig, PermitSignature, YieldFarmingClient, YieldFarmingError};
+
+/// A local private key used to sign transactions for RPC providers that don't hold the account
+pub struct Signer {
+    key: SecretKey,
+    address: Address,
+    chain_id: u64,
+}
+
+impl Signer {
+    /// Create a signer for `chain_id`, which is embedded in signatures for EIP-155 replay protection
+    pub fn new(key: SecretKey, chain_id: u64) -> Self {
+        let address = SecretKeyRef::new(&key).address();
+        Self { key, address, chain_id }
+    }
+
+    /// Create a signer from a hex-encoded private key, with or without the `0x` prefix
+    pub fn from_hex(private_key: &str, chain_id: u64) -> Result<Self, YieldFarmingError> {
+        let bytes = hex::decode(private_key.trim_start_matches("0x")).map_err(|_| YieldFarmingError::InvalidPrivateKey)?;
+        let key = SecretKey::from_slice(&bytes).map_err(|_| YieldFarmingError::InvalidPrivateKey)?;
+        Ok(Self::new(key, chain_id))
+    }
+
+    /// Address derived from the private key
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Chain the signer produces transactions for
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    /// Sign a digest from `YieldFarmingClient::permit_digest`
+    pub fn sign_permit(&self, digest: H256) -> Result<PermitSignature, YieldFarmingError> {
+        let signature = SecretKeyRef::new(&self.key)
+            .sign(digest.as_bytes(), None)
+            .map_err(|_| YieldFarmingError::InvalidPrivateKey)?;
+        Ok(PermitSignature {
+            v: signature.v as u8,
+            r: signature.r,
+            s: signature.s,
+        })
+    }
+}
+
+// Never print the private key
+impl fmt::Debug for Signer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Signer")
+            .field("address", &self.address)
+            .field("chain_id", &self.chain_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Deposit tokens, signing the transaction locally
+    pub async fn deposit_signed(&self, amount: U256, account: Address, signer: &Signer) -> Result<H256, YieldFarmingError> {
+        self.deposit_signed_with_gas(amount, account, signer, &GasConfig::default()).await
+    }
+
+    /// Deposit tokens, signing the transaction locally with explicit gas settings
+    pub async fn deposit_signed_with_gas(&self, amount: U256, account: Address, signer: &Signer, gas: &GasConfig) -> Result<H256, YieldFarmingError> {
+        self.ensure_not_paused(gas).await?;
+        self.deposit_limits().await?.check(amount)?;
+        self.ensure_capacity(amount).await?;
+        self.send_signed("deposit", (amount,), account, signer, gas).await
+    }
+
+    /// Withdraw tokens, signing the transaction locally
+    pub async fn withdraw_signed(&self, amount: U256, account: Address, signer: &Signer) -> Result<H256, YieldFarmingError> {
+        self.send_signed("withdraw", (amount,), account, signer, &GasConfig::default()).await
+    }
+
+    /// Claim rewards, signing the transaction locally
+    pub async fn claim_rewards_signed(&self, account: Address, signer: &Signer) -> Result<H256, YieldFarmingError> {
+        self.send_signed("claimRewards", (), account, signer, &GasConfig::default()).await
+    }
+
+    /// Build, sign and submit a raw transaction calling `func` on the farming contract
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, params, signer, gas), fields(?account), err))]
+    pub(crate) async fn send_signed(
+        &self,
+        func: &str,
+        params: impl Tokenize,
+        account: Address,
+        signer: &Signer,
+        gas: &GasConfig,
+    ) -> Result<H256, YieldFarmingError> {
+        if signer.address() != account {
+            return Err(YieldFarmingError::SignerMismatch {
+                expected: account,
+                actual: signer.address(),
+            });
+        }
+
+        let tokens = params.into_tokens();
+        let data = self.contract.abi().function(func)?.encode_input(&tokens)?;
+        let options = gas.to_options();
+
+        // Without an explicit limit, estimate instead of relying on the transaction default
+        let gas_limit = match options.gas {
+            Some(gas_limit) => gas_limit,
+            None => gas.buffered_gas_limit(
+                retry::with_timeout(self.request_timeout, async {
+                    self.contract
+                        .estimate_gas(func, tokens.as_slice(), account, Options::default())
+                        .await
+                        .map_err(|err| self.contract_error(err))
+                })
+                .await?,
+            ),
+        };
+
+        let tx = TransactionParameters {
+            nonce: options.nonce,
+            to: Some(self.contract.address()),
+            gas: gas_limit,
+            gas_price: options.gas_price,
+            value: options.value.unwrap_or_default(),
+            data: Bytes(data),
+            chain_id: Some(signer.chain_id()),
+            transaction_type: options.transaction_type,
+            max_fee_per_gas: options.max_fee_per_gas,
+            max_priority_fee_per_gas: options.max_priority_fee_per_gas,
+            ..TransactionParameters::default()
+        };
+
+        // Fills in a missing nonce or gas price from the node
+        let signed = retry::with_timeout(self.request_timeout, async {
+            Ok(self.web3.accounts().sign_transaction(tx, SecretKeyRef::new(&signer.key)).await?)
+        })
+        .await?;
+        trace::debug!(method = "eth_sendRawTransaction", func, "submitting signed transaction");
+        let tx_hash = telemetry::rpc("eth_sendRawTransaction", retry::with_timeout(self.request_timeout, async {
+            Ok(self.web3.eth().send_raw_transaction(signed.raw_transaction).await?)
+        }))
+        .await?;
+        telemetry::tx_submitted();
+
+        trace::info!(func, ?tx_hash, "transaction submitted");
+        Ok(tx_hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_signer_derives_address() {
+        // Well-known development key #0 used by Hardhat and Anvil
+        let signer = Signer::from_hex("0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80", 31337).unwrap();
+        assert_eq!(
+            signer.address(),
+            Address::from_str("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266").unwrap()
+        );
+        assert!(!format!("{:?}", signer).contains("ac0974"));
+    }
+
+    #[test]
+    fn test_sign_permit_recovers_signer() {
+        let signer = Signer::from_hex("0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80", 1).unwrap();
+        let digest = H256::repeat_byte(0x42);
+        let signature = signer.sign_permit(digest).unwrap();
+        assert!(signature.v == 27 || signature.v == 28);
+
+        let rs = [signature.r.as_bytes(), signature.s.as_bytes()].concat();
+        let recovered = web3::signing::recover(digest.as_bytes(), &rs, i32::from(signature.v) - 27).unwrap();
+        assert_eq!(recovered, signer.address());
+    }
+
+    #[tokio::test]
+    async fn test_deposit_signed_checks_pause() {
+        const ABI: &[u8] = br#"[
+            {"type":"function","name":"deposit","stateMutability":"nonpayable","inputs":[{"name":"amount","type":"uint256"}],"outputs":[]},
+            {"type":"function","name":"paused","stateMutability":"view","inputs":[],"outputs":[{"name":"","type":"bool"}]}
+        ]"#;
+        let signer = Signer::from_hex("0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80", 1).unwrap();
+        let transport = crate::testing::MockTransport::new();
+        transport.push_call_result(&[web3::ethabi::Token::Bool(true)]);
+        let client = YieldFarmingClient::from_transport(transport.clone(), Address::zero(), ABI).unwrap();
+
+        let gas = GasConfig { check_paused: true, ..GasConfig::default() };
+        let paused = client.deposit_signed_with_gas(1.into(), signer.address(), &signer, &gas).await;
+        assert!(matches!(paused, Err(YieldFarmingError::PoolPaused)));
+    }
+
+    #[test]
+    fn test_signer_rejects_bad_key() {
+        assert!(matches!(Signer::from_hex("not-a-key", 1), Err(YieldFarmingError::InvalidPrivateKey)));
+    }
+}
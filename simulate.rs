@@ -0,0 +1,91 @@
+use web3::{
+    contract::{tokens::Tokenize, Options},
+    types::{Address, BlockId, BlockNumber, Bytes, CallRequest, U256},
+    Transport,
+};
+
+use crate::{retry, YieldFarmingClient, YieldFarmingError};
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Check whether a deposit would succeed without sending it
+    pub async fn simulate_deposit(&self, amount: U256, account: Address) -> Result<(), YieldFarmingError> {
+        self.simulate_call("deposit", (amount,), account, Options::default()).await?;
+        Ok(())
+    }
+
+    /// Check whether a withdrawal would succeed without sending it
+    pub async fn simulate_withdraw(&self, amount: U256, account: Address) -> Result<(), YieldFarmingError> {
+        self.simulate_call("withdraw", (amount,), account, Options::default()).await?;
+        Ok(())
+    }
+
+    /// Check whether claiming rewards would succeed without sending it
+    pub async fn simulate_claim_rewards(&self, account: Address) -> Result<(), YieldFarmingError> {
+        self.simulate_call("claimRewards", (), account, Options::default()).await?;
+        Ok(())
+    }
+
+    /// Execute `func` with `eth_call` at the latest block and return the raw output
+    pub(crate) async fn simulate_call(
+        &self,
+        func: &str,
+        params: impl Tokenize,
+        account: Address,
+        options: Options,
+    ) -> Result<Bytes, YieldFarmingError> {
+        let data = self.contract.abi().function(func)?.encode_input(&params.into_tokens())?;
+        self.simulate_raw(data, account, options).await
+    }
+
+    /// Execute pre-encoded call data with `eth_call` at the latest block
+    pub(crate) async fn simulate_raw(&self, data: Vec<u8>, account: Address, options: Options) -> Result<Bytes, YieldFarmingError> {
+        let request = CallRequest {
+            from: Some(account),
+            to: Some(self.contract.address()),
+            gas: options.gas,
+            value: options.value,
+            data: Some(Bytes(data)),
+            ..CallRequest::default()
+        };
+
+        retry::with_timeout(self.request_timeout, async {
+            self.web3.eth()
+                .call(request, Some(BlockId::Number(BlockNumber::Latest)))
+                .await
+                .map_err(|err| self.rpc_error(err))
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTransport;
+    use web3::types::H256;
+
+    const POOL_ABI: &[u8] = br#"[
+        {"type":"function","name":"deposit","stateMutability":"nonpayable","inputs":[{"name":"amount","type":"uint256"}],"outputs":[]}
+    ]"#;
+
+    fn sends(transport: &MockTransport) -> usize {
+        transport.requests().iter().filter(|(method, _)| method == "eth_sendTransaction").count()
+    }
+
+    #[tokio::test]
+    async fn test_simulate_before_send() {
+        let transport = MockTransport::new();
+        transport.set_response("eth_estimateGas", serde_json::json!("0x5208"));
+        transport.set_response("eth_sendTransaction", serde_json::to_value(H256::repeat_byte(0xab)).unwrap());
+        let mut client = YieldFarmingClient::from_transport(transport.clone(), Address::zero(), POOL_ABI).unwrap();
+        client.simulate_before_send = true;
+
+        // No eth_call response, so the dry run fails and nothing is sent
+        assert!(client.deposit(100.into(), Address::repeat_byte(0x01)).await.is_err());
+        assert_eq!(sends(&transport), 0);
+
+        transport.push_response("eth_call", serde_json::json!("0x"));
+        client.deposit(100.into(), Address::repeat_byte(0x01)).await.unwrap();
+        assert_eq!(sends(&transport), 1);
+    }
+}
@@ -0,0 +1,201 @@
+use web3::{
+    contract::{tokens::Tokenize, Options},
+    ethabi::{Function, ParamType, Token},
+    types::{Address, H256, U256},
+    Transport,
+};
+
+use crate::{GasConfig, YieldFarmingClient, YieldFarmingError};
+
+/// Result of a deposit sent with a minimum-shares guard
+#[derive(Debug, Clone, PartialEq)]
+pub struct MinOutDeposit {
+    pub tx_hash: H256,
+    /// Shares the deposit was simulated to mint, when the contract returns them
+    pub expected_shares: Option<U256>,
+}
+
+/// Result of a withdrawal sent with a minimum-underlying guard
+#[derive(Debug, Clone, PartialEq)]
+pub struct MinOutWithdraw {
+    pub tx_hash: H256,
+    /// Underlying tokens the withdrawal was simulated to return, when the contract returns them
+    pub expected_underlying: Option<U256>,
+}
+
+/// Outcome of `min_out_call`
+enum MinOutCall {
+    /// Sent through an overload enforcing the minimum on-chain, with the simulated amount if returned
+    Sent(H256, Option<U256>),
+    /// No such overload; the plain call was only simulated and returned this amount, if any
+    Simulated(Option<U256>),
+}
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Deposit `amount`, refusing to proceed if fewer than `min_shares` would be minted.
+    ///
+    /// Prefers a `deposit(uint256,uint256)` overload whose second input is named
+    /// as a minimum (e.g. `minShares`), which enforces the minimum on-chain;
+    /// MasterChef-style `deposit(pid, amount)` is never mistaken for one. Without
+    /// it, the plain `deposit` is simulated and rejected client-side with
+    /// `SlippageExceeded`; that check cannot guard against the pool changing
+    /// before the transaction is mined.
+    pub async fn deposit_with_min_out(&self, amount: U256, min_shares: U256, account: Address) -> Result<MinOutDeposit, YieldFarmingError> {
+        self.deposit_with_min_out_with_gas(amount, min_shares, account, &GasConfig::default()).await
+    }
+
+    /// Like `deposit_with_min_out`, with explicit gas settings, checking pause state if `gas` asks to
+    pub async fn deposit_with_min_out_with_gas(
+        &self,
+        amount: U256,
+        min_shares: U256,
+        account: Address,
+        gas: &GasConfig,
+    ) -> Result<MinOutDeposit, YieldFarmingError> {
+        self.ensure_not_paused(gas).await?;
+        self.deposit_limits().await?.check(amount)?;
+        self.ensure_capacity(amount).await?;
+
+        let expected = match self.min_out_call("deposit", amount, min_shares, account, gas.to_options()).await? {
+            MinOutCall::Sent(tx_hash, expected_shares) => {
+                let tx_hash = self.enforce_deadline(tx_hash, gas.valid_until).await?;
+                return Ok(MinOutDeposit { tx_hash, expected_shares });
+            }
+            MinOutCall::Simulated(expected) => expected.ok_or_else(|| unreported_amount("deposit does not return the number of shares minted"))?,
+        };
+        if expected < min_shares {
+            return Err(YieldFarmingError::SlippageExceeded { expected, min: min_shares });
+        }
+
+        // Limits and capacity were checked above, so skip `deposit_with_gas`'s second round
+        let options = self.options_with_gas_limit("deposit", (amount,), account, gas).await?;
+        let tx_hash = self.submit("deposit", (amount,), account, options).await?;
+        let tx_hash = self.enforce_deadline(tx_hash, gas.valid_until).await?;
+        Ok(MinOutDeposit { tx_hash, expected_shares: Some(expected) })
+    }
+
+    /// Burn `shares`, refusing to proceed if fewer than `min_underlying` tokens would be returned.
+    ///
+    /// Prefers a `withdraw(uint256,uint256)` overload whose second input is
+    /// named as a minimum (e.g. `minAssets`), which enforces the minimum
+    /// on-chain; MasterChef-style `withdraw(pid, amount)` is never mistaken for one. Without one, the plain `withdraw` is simulated and
+    /// rejected client-side with `UnderlyingBelowMinimum`; that check cannot
+    /// guard against the pool changing before the transaction is mined.
+    pub async fn withdraw_with_min_out(&self, shares: U256, min_underlying: U256, account: Address) -> Result<MinOutWithdraw, YieldFarmingError> {
+        let expected = match self.min_out_call("withdraw", shares, min_underlying, account, Options::default()).await? {
+            MinOutCall::Sent(tx_hash, expected_underlying) => return Ok(MinOutWithdraw { tx_hash, expected_underlying }),
+            MinOutCall::Simulated(expected) => expected.ok_or_else(|| unreported_amount("withdraw does not return the amount of underlying tokens"))?,
+        };
+        if expected < min_underlying {
+            return Err(YieldFarmingError::UnderlyingBelowMinimum { expected, min: min_underlying });
+        }
+
+        let tx_hash = self.withdraw(shares, account).await?;
+        Ok(MinOutWithdraw { tx_hash, expected_underlying: Some(expected) })
+    }
+
+    /// Send `func(amount, min)` if the pool has that overload, otherwise simulate the plain `func(amount)`
+    async fn min_out_call(&self, func: &str, amount: U256, min: U256, account: Address, options: Options) -> Result<MinOutCall, YieldFarmingError> {
+        let abi = self.contract.abi();
+        if let Some(function) = abi.functions_by_name(func)?.iter().find(|function| is_min_out_overload(function)) {
+            let params = (amount, min).into_tokens();
+            let output = self.simulate_raw(function.encode_input(&params)?, account, Options::default()).await?;
+            let expected = decode_shares(function, &output.0);
+
+            let tx_hash = self.submit_overload(function, params, account, options).await?;
+            return Ok(MinOutCall::Sent(tx_hash, expected));
+        }
+
+        let output = self.simulate_call(func, (amount,), account, Options::default()).await?;
+        Ok(MinOutCall::Simulated(decode_shares(abi.function(func)?, &output.0)))
+    }
+}
+
+/// Whether `function` is `(uint256, uint256)` with a second input named as a minimum, like `minShares`
+fn is_min_out_overload(function: &Function) -> bool {
+    match function.inputs.as_slice() {
+        [amount, min] => {
+            amount.kind == ParamType::Uint(256) && min.kind == ParamType::Uint(256) && min.name.to_lowercase().contains("min")
+        }
+        _ => false,
+    }
+}
+
+fn unreported_amount(message: &str) -> YieldFarmingError {
+    YieldFarmingError::Contract(web3::contract::Error::InvalidOutputType(message.into()))
+}
+
+/// Read the first `uint` return value of `function`, e.g. shares minted or tokens returned, from raw call output
+fn decode_shares(function: &Function, output: &[u8]) -> Option<U256> {
+    match function.decode_output(output).ok()?.into_iter().next()? {
+        Token::Uint(shares) => Some(shares),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use web3::ethabi::{self, Contract as ContractABI};
+
+    #[test]
+    fn test_decode_shares() {
+        let abi = ContractABI::load(
+            br#"[{"type":"function","name":"deposit","stateMutability":"nonpayable","inputs":[{"name":"amount","type":"uint256"}],"outputs":[{"name":"shares","type":"uint256"}]}]"#
+                .as_slice(),
+        )
+        .unwrap();
+        let function = abi.function("deposit").unwrap();
+
+        let output = ethabi::encode(&[Token::Uint(42.into())]);
+        assert_eq!(decode_shares(function, &output), Some(42.into()));
+        assert_eq!(decode_shares(function, &[]), None);
+    }
+
+    #[test]
+    fn test_min_out_overload_needs_named_minimum() {
+        let abi = ContractABI::load(
+            br#"[
+                {"type":"function","name":"deposit","stateMutability":"nonpayable","inputs":[{"name":"pid","type":"uint256"},{"name":"amount","type":"uint256"}],"outputs":[]},
+                {"type":"function","name":"deposit","stateMutability":"nonpayable","inputs":[{"name":"amount","type":"uint256"},{"name":"minShares","type":"uint256"}],"outputs":[]},
+                {"type":"function","name":"withdraw","stateMutability":"nonpayable","inputs":[{"name":"pid","type":"uint256"},{"name":"amount","type":"uint256"}],"outputs":[]},
+                {"type":"function","name":"withdraw","stateMutability":"nonpayable","inputs":[{"name":"shares","type":"uint256"},{"name":"minAssets","type":"uint256"}],"outputs":[]}
+            ]"#
+            .as_slice(),
+        )
+        .unwrap();
+        for func in ["deposit", "withdraw"] {
+            let overloads = abi.functions_by_name(func).unwrap();
+            assert!(!is_min_out_overload(&overloads[0]));
+            assert!(is_min_out_overload(&overloads[1]));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deposit_with_min_out_rejects_paused_pool() {
+        let transport = crate::testing::MockTransport::new();
+        transport.push_call_result(&[Token::Bool(true)]);
+        let abi = br#"[
+            {"type":"function","name":"deposit","stateMutability":"nonpayable","inputs":[{"name":"amount","type":"uint256"}],"outputs":[{"name":"shares","type":"uint256"}]},
+            {"type":"function","name":"paused","stateMutability":"view","inputs":[],"outputs":[{"name":"","type":"bool"}]}
+        ]"#;
+        let client = YieldFarmingClient::from_transport(transport.clone(), Address::zero(), abi).unwrap();
+
+        let gas = GasConfig { check_paused: true, ..GasConfig::default() };
+        let result = client.deposit_with_min_out_with_gas(100.into(), 95.into(), Address::repeat_byte(0x01), &gas).await;
+        assert!(matches!(result, Err(YieldFarmingError::PoolPaused)));
+        assert_eq!(transport.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_withdraw_rejected_below_min_underlying() {
+        let transport = crate::testing::MockTransport::new();
+        transport.push_call_result(&[Token::Uint(90.into())]);
+        let abi = br#"[{"type":"function","name":"withdraw","stateMutability":"nonpayable","inputs":[{"name":"shares","type":"uint256"}],"outputs":[{"name":"assets","type":"uint256"}]}]"#;
+        let client = YieldFarmingClient::from_transport(transport.clone(), Address::zero(), abi).unwrap();
+
+        let result = client.withdraw_with_min_out(100.into(), 95.into(), Address::repeat_byte(0x01)).await;
+        assert!(matches!(result, Err(YieldFarmingError::UnderlyingBelowMinimum { expected, min }) if expected == 90.into() && min == 95.into()));
+        assert!(transport.requests().iter().all(|(method, _)| method == "eth_call"));
+    }
+}
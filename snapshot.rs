@@ -0,0 +1,239 @@
+use serde::{Serialize, Serializer};
+use web3::{
+    types::{Address, BlockId, BlockNumber, U256},
+    Transport,
+};
+
+use crate::{retry, UserPosition, YieldFarmingClient, YieldFarmingError};
+
+/// Pool and user state captured at a single block
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PoolSnapshot {
+    pub pool: Address,
+    pub account: Address,
+    pub block_number: u64,
+    /// Unix timestamp of `block_number`
+    pub timestamp: u64,
+    /// Base fee per gas of `block_number`, `None` on chains without EIP-1559
+    #[serde(serialize_with = "serialize_opt_u256")]
+    pub base_fee: Option<U256>,
+    pub position: UserPosition,
+}
+
+/// Change in a user's position between two blocks, from `diff_position`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PositionDiff {
+    pub block_a: u64,
+    pub block_b: u64,
+    /// Seconds between the two blocks' timestamps
+    pub elapsed_seconds: u64,
+    /// Staked balance at `block_a` and `block_b`
+    #[serde(serialize_with = "serialize_u256_pair")]
+    pub staked_balance: (U256, U256),
+    /// Pending rewards at `block_a` and `block_b`
+    #[serde(serialize_with = "serialize_u256_pair")]
+    pub pending_rewards: (U256, U256),
+}
+
+impl PositionDiff {
+    /// Change in staked balance from `block_a` to `block_b`, and whether it was a decrease
+    pub fn staked_delta(&self) -> (U256, bool) {
+        delta(self.staked_balance)
+    }
+
+    /// Change in pending rewards from `block_a` to `block_b`, and whether it was a decrease.
+    ///
+    /// A claim between the two blocks resets pending rewards, so this understates what was earned.
+    pub fn rewards_delta(&self) -> (U256, bool) {
+        delta(self.pending_rewards)
+    }
+}
+
+fn delta((before, after): (U256, U256)) -> (U256, bool) {
+    if after >= before {
+        (after - before, false)
+    } else {
+        (before - after, true)
+    }
+}
+
+/// Serialize a `U256` as a decimal string so large values survive JSON consumers
+pub(crate) fn serialize_u256<S: Serializer>(value: &U256, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&value.to_string())
+}
+
+/// Serialize an optional `U256` as a decimal string or `null`
+pub(crate) fn serialize_opt_u256<S: Serializer>(value: &Option<U256>, serializer: S) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(value) => serialize_u256(value, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn serialize_u256_pair<S: Serializer>(value: &(U256, U256), serializer: S) -> Result<S::Ok, S::Error> {
+    [value.0.to_string(), value.1.to_string()].serialize(serializer)
+}
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Capture a serializable snapshot of the pool and `account`'s position.
+    ///
+    /// Every view is read at the reported block, bypassing the view cache, so
+    /// the snapshot is consistent and can be reproduced later on an archive node.
+    pub async fn snapshot(&self, account: Address) -> Result<PoolSnapshot, YieldFarmingError> {
+        let block = self.retry_policy
+            .retry(|| retry::with_timeout(self.request_timeout, async {
+                Ok(self.web3.eth().block(BlockId::Number(BlockNumber::Latest)).await?)
+            }))
+            .await?
+            .ok_or(YieldFarmingError::BlockNotFound)?;
+        let block_number = block.number.ok_or(YieldFarmingError::BlockNotFound)?.as_u64();
+        let position = self.position_at(account, BlockNumber::Number(block_number.into())).await?;
+
+        Ok(PoolSnapshot {
+            pool: self.contract.address(),
+            account,
+            block_number,
+            timestamp: block.timestamp.as_u64(),
+            base_fee: block.base_fee_per_gas,
+            position,
+        })
+    }
+
+    /// `account`'s position with every figure read at `block`
+    async fn position_at(&self, account: Address, block: BlockNumber) -> Result<UserPosition, YieldFarmingError> {
+        let current_apy = async {
+            match self.contract.abi().function("getCurrentAPY") {
+                Ok(_) => self.query_at_with_retry("getCurrentAPY", (), Some(block)).await.map(Some),
+                Err(_) => Ok(None),
+            }
+        };
+        let (staked_balance, pending_rewards, total_value_locked, current_apy) = futures::try_join!(
+            self.get_staked_balance_at(account, block),
+            self.query_at_with_retry::<U256, _>("pendingRewards", (account,), Some(block)),
+            self.tvl_at(block),
+            current_apy,
+        )?;
+
+        Ok(UserPosition {
+            staked_balance,
+            pending_rewards,
+            total_value_locked,
+            current_apy,
+        })
+    }
+
+    /// Compare `account`'s staked balance and pending rewards at `block_a` and `block_b`.
+    ///
+    /// `block_b` is expected to be the later block. Reading past state needs an
+    /// archive node; a pruning node fails with `StateUnavailable`.
+    pub async fn diff_position(&self, account: Address, block_a: BlockNumber, block_b: BlockNumber) -> Result<PositionDiff, YieldFarmingError> {
+        let ((number_a, time_a), (number_b, time_b), staked_a, staked_b, pending_a, pending_b) = futures::try_join!(
+            self.block_header(block_a),
+            self.block_header(block_b),
+            self.get_staked_balance_at(account, block_a),
+            self.get_staked_balance_at(account, block_b),
+            self.query_at_with_retry::<U256, _>("pendingRewards", (account,), Some(block_a)),
+            self.query_at_with_retry::<U256, _>("pendingRewards", (account,), Some(block_b)),
+        )?;
+
+        Ok(PositionDiff {
+            block_a: number_a,
+            block_b: number_b,
+            elapsed_seconds: time_b.saturating_sub(time_a),
+            staked_balance: (staked_a, staked_b),
+            pending_rewards: (pending_a, pending_b),
+        })
+    }
+
+    /// Number and timestamp of `block`
+    async fn block_header(&self, block: BlockNumber) -> Result<(u64, u64), YieldFarmingError> {
+        let block = self.retry_policy
+            .retry(|| retry::with_timeout(self.request_timeout, async {
+                Ok(self.web3.eth().block(BlockId::Number(block)).await?)
+            }))
+            .await?
+            .ok_or(YieldFarmingError::BlockNotFound)?;
+        let number = block.number.ok_or(YieldFarmingError::BlockNotFound)?.as_u64();
+        Ok((number, block.timestamp.as_u64()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_serializes_u256_as_decimal() {
+        let snapshot = PoolSnapshot {
+            pool: Address::zero(),
+            account: Address::zero(),
+            block_number: 100,
+            timestamp: 1_700_000_000,
+            base_fee: None,
+            position: UserPosition {
+                staked_balance: U256::MAX,
+                pending_rewards: 1.into(),
+                total_value_locked: 0.into(),
+                current_apy: Some(12.into()),
+            },
+        };
+
+        let json = serde_json::to_value(&snapshot).unwrap();
+        assert_eq!(json["position"]["staked_balance"], U256::MAX.to_string());
+        assert_eq!(json["position"]["current_apy"], "12");
+        assert_eq!(json["block_number"], 100);
+        assert!(json["base_fee"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_reads_views_at_reported_block() {
+        let transport = crate::testing::MockTransport::new();
+        let block = web3::types::Block::<web3::types::H256> {
+            number: Some(100u64.into()),
+            ..Default::default()
+        };
+        transport.set_response("eth_getBlockByNumber", serde_json::to_value(block).unwrap());
+        transport.set_response("eth_call", serde_json::to_value(web3::types::Bytes(web3::ethabi::encode(&[web3::ethabi::Token::Uint(1.into())]))).unwrap());
+        let abi = br#"[
+            {"type":"function","name":"balanceOf","stateMutability":"view","inputs":[{"name":"account","type":"address"}],"outputs":[{"name":"","type":"uint256"}]},
+            {"type":"function","name":"pendingRewards","stateMutability":"view","inputs":[{"name":"account","type":"address"}],"outputs":[{"name":"","type":"uint256"}]},
+            {"type":"function","name":"totalValueLocked","stateMutability":"view","inputs":[],"outputs":[{"name":"","type":"uint256"}]}
+        ]"#;
+
+        let client = YieldFarmingClient::from_transport(transport.clone(), Address::zero(), abi).unwrap();
+        let snapshot = client.snapshot(Address::repeat_byte(0x01)).await.unwrap();
+        assert_eq!(snapshot.position.current_apy, None);
+
+        let calls: Vec<_> = transport.requests().into_iter().filter(|(method, _)| method == "eth_call").collect();
+        assert_eq!(calls.len(), 3);
+        assert!(calls.iter().all(|(_, params)| params[1] == "0x64"));
+    }
+
+    #[tokio::test]
+    async fn test_diff_position_between_blocks() {
+        let transport = crate::testing::MockTransport::new();
+        let block = |number: u64, timestamp: u64| {
+            let block = web3::types::Block::<web3::types::H256> {
+                number: Some(number.into()),
+                timestamp: timestamp.into(),
+                ..Default::default()
+            };
+            serde_json::to_value(block).unwrap()
+        };
+        transport.push_response("eth_getBlockByNumber", block(100, 1_000));
+        transport.push_response("eth_getBlockByNumber", block(200, 2_200));
+        for amount in [500u64, 400, 10, 70] {
+            transport.push_call_result(&[web3::ethabi::Token::Uint(amount.into())]);
+        }
+        let abi = br#"[
+            {"type":"function","name":"balanceOf","stateMutability":"view","inputs":[{"name":"account","type":"address"}],"outputs":[{"name":"","type":"uint256"}]},
+            {"type":"function","name":"pendingRewards","stateMutability":"view","inputs":[{"name":"account","type":"address"}],"outputs":[{"name":"","type":"uint256"}]}
+        ]"#;
+
+        let client = YieldFarmingClient::from_transport(transport, Address::zero(), abi).unwrap();
+        let diff = client.diff_position(Address::repeat_byte(0x01), 100.into(), 200.into()).await.unwrap();
+        assert_eq!((diff.block_a, diff.block_b, diff.elapsed_seconds), (100, 200, 1_200));
+        assert_eq!(diff.staked_delta(), (100.into(), true));
+        assert_eq!(diff.rewards_delta(), (60.into(), false));
+    }
+}
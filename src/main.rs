@@ -0,0 +1,61 @@
+//! Example program connecting a `YieldFarmingClient` to a pool.
+
+use std::str::FromStr;
+use web3::types::Address;
+
+use blockchain_yield_farming::YieldFarmingClient;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Configuration
+    let rpc_url = "https://mainnet.infura.io/v3/YOUR_PROJECT_ID"; // Replace with your Infura project ID
+    let contract_address = Address::from_str("0x1234567890123456789012345678901234567890")?; // Replace with actual contract address
+    
+    // Initialize yield farming client
+    let client = YieldFarmingClient::new(rpc_url, contract_address, b"[]")?; // Replace with actual ABI
+    
+    println!("🚀 Yield Farming Client Initialized");
+    println!("Connected to: {}", rpc_url);
+    println!("Contract: {:?}", contract_address);
+    println!("Node reachable: {}", client.is_connected().await);
+    
+    // Example operations (commented out for safety)
+    /*
+    let account = Address::from_str("YOUR_ACCOUNT_ADDRESS")?;
+    let amount = U256::from(1000000000000000000u64); // 1 ETH in wei
+    
+    // Get current pool information
+    let tvl = client.get_total_value_locked().await?;
+    let apy = client.get_current_apy().await?;
+    let latest_block = client.get_latest_block().await?;
+    
+    println!("📊 Pool Statistics:");
+    println!("Total Value Locked: {} wei", tvl);
+    if let Some(apy) = apy {
+        println!("Current APY: {}%", apy);
+    }
+    println!("Latest Block: {}", latest_block);
+    
+    // Check user's current position
+    let staked_balance = client.get_staked_balance(account).await?;
+    let pending_rewards = client.get_pending_rewards(account).await?;
+    
+    println!("👤 User Position:");
+    println!("Staked Balance: {} wei", staked_balance);
+    println!("Pending Rewards: {} wei", pending_rewards);
+    
+    // Example deposit (uncomment to execute)
+    // let tx_hash = client.deposit(amount, account).await?;
+    // println!("Deposit transaction: {:?}", tx_hash);
+    // 
+    // let receipt = client.wait_for_transaction(tx_hash).await?;
+    // if receipt.status == Some(1u64.into()) {
+    //     println!("✅ Deposit successful!");
+    // } else {
+    //     println!("❌ Deposit failed!");
+    // }
+    */
+    
+    println!("✅ Yield farming client ready for operations!");
+    Ok(())
+}
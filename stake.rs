@@ -0,0 +1,127 @@
+use web3::{
+    ethabi::{ParamType, Token},
+    types::{Address, BlockId, BlockNumber, Bytes, CallRequest, U256},
+    Transport,
+};
+
+use crate::{error, retry, YieldFarmingClient, YieldFarmingError};
+
+/// How `get_staked_balance` reads a user's stake
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StakeQueryStrategy {
+    /// Try `balanceOf(user)`, falling back to `userInfo` if the pool lacks it or
+    /// returns nothing decodable
+    #[default]
+    Auto,
+    /// Only use `balanceOf(user)`
+    BalanceOf,
+    /// Only read the `amount` field of `userInfo`: `userInfo(user)` when
+    /// `pool_id` is `None`, otherwise `userInfo(pool_id, user)`
+    UserInfo { pool_id: Option<U256> },
+}
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Read a user's stake at `block` (latest when `None`) according to the configured strategy
+    pub(crate) async fn query_staked_balance(&self, account: Address, block: Option<BlockNumber>) -> Result<U256, YieldFarmingError> {
+        match self.stake_query_strategy {
+            StakeQueryStrategy::BalanceOf => self.query_at_with_retry("balanceOf", (account,), block).await,
+            StakeQueryStrategy::UserInfo { pool_id } => self.user_info_amount(account, pool_id, block).await,
+            StakeQueryStrategy::Auto => {
+                if self.contract.abi().function("balanceOf").is_err() {
+                    return self.user_info_amount(account, None, block).await;
+                }
+                match self.query_at_with_retry("balanceOf", (account,), block).await {
+                    Err(err) if is_shape_mismatch(&err) && self.contract.abi().function("userInfo").is_ok() => {
+                        self.user_info_amount(account, None, block).await
+                    }
+                    result => result,
+                }
+            }
+        }
+    }
+
+    /// Decode the staked `amount` from `userInfo`, picking the overload by whether a pool id is given.
+    ///
+    /// Without an explicit pool id, a pool exposing only `userInfo(uint256,address)` is queried for pool 0.
+    async fn user_info_amount(&self, account: Address, pool_id: Option<U256>, block: Option<BlockNumber>) -> Result<U256, YieldFarmingError> {
+        let overloads = self.contract.abi().functions_by_name("userInfo")?;
+        let single = overloads.iter().find(|function| function.inputs.len() == 1);
+        let (function, params) = match (pool_id, single) {
+            (None, Some(function)) => (function, vec![Token::Address(account)]),
+            (pool_id, _) => {
+                let function = overloads
+                    .iter()
+                    .find(|function| function.inputs.len() == 2)
+                    .ok_or_else(|| user_info_error("no userInfo(uint256,address) overload in ABI"))?;
+                (function, vec![Token::Uint(pool_id.unwrap_or_default()), Token::Address(account)])
+            }
+        };
+
+        let request = CallRequest {
+            to: Some(self.contract.address()),
+            data: Some(Bytes(function.encode_input(&params)?)),
+            ..CallRequest::default()
+        };
+        let block = block.unwrap_or(BlockNumber::Latest);
+        let output = self.retry_policy
+            .retry(|| retry::with_timeout(self.request_timeout, async {
+                self.web3.eth()
+                    .call(request.clone(), Some(BlockId::Number(block)))
+                    .await
+                    .map_err(|err| match web3::contract::Error::Api(err) {
+                        err if error::is_missing_state(&err) => YieldFarmingError::StateUnavailable(block),
+                        err => YieldFarmingError::from(err),
+                    })
+            }))
+            .await?;
+
+        let amount_index = function.outputs.iter().position(|output| output.name == "amount").unwrap_or(0);
+        let tokens = function.decode_output(&output.0)?;
+        let amount = match tokens.as_slice() {
+            // A struct return value decodes as a single tuple whose first field is `amount` by convention
+            [Token::Tuple(fields)] if matches!(function.outputs[0].kind, ParamType::Tuple(_)) => fields.first(),
+            tokens => tokens.get(amount_index),
+        };
+        match amount {
+            Some(Token::Uint(amount)) => Ok(*amount),
+            _ => Err(user_info_error("userInfo did not return a uint256 amount")),
+        }
+    }
+}
+
+/// Whether `balanceOf` failed because the pool doesn't implement it, rather than a node or revert error
+fn is_shape_mismatch(err: &YieldFarmingError) -> bool {
+    matches!(
+        err,
+        YieldFarmingError::AbiParse(_)
+            | YieldFarmingError::Contract(web3::contract::Error::Abi(_))
+            | YieldFarmingError::Contract(web3::contract::Error::InvalidOutputType(_))
+    )
+}
+
+fn user_info_error(message: &str) -> YieldFarmingError {
+    YieldFarmingError::Contract(web3::contract::Error::InvalidOutputType(message.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTransport;
+
+    const USER_INFO_ABI: &[u8] = br#"[
+        {"type":"function","name":"balanceOf","stateMutability":"view","inputs":[{"name":"account","type":"address"}],"outputs":[{"name":"","type":"uint256"}]},
+        {"type":"function","name":"userInfo","stateMutability":"view","inputs":[{"name":"user","type":"address"}],"outputs":[{"name":"rewardDebt","type":"uint256"},{"name":"amount","type":"uint256"}]}
+    ]"#;
+
+    #[tokio::test]
+    async fn test_auto_falls_back_to_user_info() {
+        let transport = MockTransport::new();
+        // `balanceOf` on a contract without it returns empty data
+        transport.push_response("eth_call", serde_json::json!("0x"));
+        transport.push_call_result(&[Token::Uint(3.into()), Token::Uint(500.into())]);
+
+        let client = YieldFarmingClient::from_transport(transport.clone(), Address::zero(), USER_INFO_ABI).unwrap();
+        assert_eq!(client.get_staked_balance(Address::zero()).await.unwrap(), U256::from(500));
+        assert_eq!(transport.requests().len(), 2);
+    }
+}
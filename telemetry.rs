@@ -0,0 +1,58 @@
+//! Counters and histograms recorded through the `metrics` facade when the `metrics` feature is
+//! enabled. Without the feature every hook here compiles to nothing.
+
+use std::future::Future;
+
+/// RPC requests issued, labelled by `method` and `status` (`ok` or `error`)
+pub const RPC_CALLS_TOTAL: &str = "rpc_calls_total";
+
+/// Latency of RPC requests in seconds, labelled by `method`
+pub const RPC_LATENCY_SECONDS: &str = "rpc_latency_seconds";
+
+/// Transactions accepted by the node
+pub const TX_SUBMITTED_TOTAL: &str = "tx_submitted_total";
+
+/// Transactions observed to revert after being mined
+pub const TX_REVERTED_TOTAL: &str = "tx_reverted_total";
+
+/// Describe the client's metrics to the installed recorder, e.g. a Prometheus exporter.
+///
+/// Call once after installing the recorder so exported metrics carry help text and units.
+#[cfg(feature = "metrics")]
+pub fn register_metrics() {
+    use metrics::{describe_counter, describe_histogram, Unit};
+
+    describe_counter!(RPC_CALLS_TOTAL, Unit::Count, "RPC requests issued by the yield farming client");
+    describe_histogram!(RPC_LATENCY_SECONDS, Unit::Seconds, "Latency of RPC requests issued by the yield farming client");
+    describe_counter!(TX_SUBMITTED_TOTAL, Unit::Count, "Transactions submitted by the yield farming client");
+    describe_counter!(TX_REVERTED_TOTAL, Unit::Count, "Submitted transactions that reverted");
+}
+
+/// Await an RPC request, recording its outcome and latency under `method`
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) async fn rpc<R, E>(method: &'static str, request: impl Future<Output = Result<R, E>>) -> Result<R, E> {
+    #[cfg(feature = "metrics")]
+    let started = std::time::Instant::now();
+    let result = request.await;
+
+    #[cfg(feature = "metrics")]
+    {
+        let status = if result.is_ok() { "ok" } else { "error" };
+        metrics::counter!(RPC_CALLS_TOTAL, "method" => method, "status" => status).increment(1);
+        metrics::histogram!(RPC_LATENCY_SECONDS, "method" => method).record(started.elapsed().as_secs_f64());
+    }
+
+    result
+}
+
+/// Record a transaction accepted by the node
+pub(crate) fn tx_submitted() {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(TX_SUBMITTED_TOTAL).increment(1);
+}
+
+/// Record a mined transaction that reverted
+pub(crate) fn tx_reverted() {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(TX_REVERTED_TOTAL).increment(1);
+}
@@ -0,0 +1,137 @@
+//! In-memory transport for unit testing without a node.
+//!
+//! Enabled with the `testing` feature.
+
+use futures::future::{self, BoxFuture, FutureExt};
+use jsonrpc_core as rpc;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use web3::{
+    ethabi::{self, Token},
+    helpers,
+    types::Bytes,
+    RequestId, Transport,
+};
+
+#[derive(Debug, Default)]
+struct MockState {
+    queued: HashMap<String, VecDeque<rpc::Value>>,
+    fixed: HashMap<String, rpc::Value>,
+    requests: Vec<(String, Vec<rpc::Value>)>,
+}
+
+/// Transport that answers requests with canned responses keyed by JSON-RPC method.
+///
+/// Clones share state, so a handle kept by the test can still queue responses
+/// and inspect requests after the transport has been moved into a client.
+#[derive(Debug, Default, Clone)]
+pub struct MockTransport {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Always answer `method` with `value` once its queued responses are used up
+    pub fn set_response(&self, method: &str, value: rpc::Value) {
+        self.lock().fixed.insert(method.to_string(), value);
+    }
+
+    /// Answer the next unanswered `method` request with `value`
+    pub fn push_response(&self, method: &str, value: rpc::Value) {
+        self.lock().queued.entry(method.to_string()).or_default().push_back(value);
+    }
+
+    /// Answer the next `eth_call` with the ABI encoding of `tokens`
+    pub fn push_call_result(&self, tokens: &[Token]) {
+        let encoded = Bytes(ethabi::encode(tokens));
+        self.push_response("eth_call", serde_json::to_value(encoded).expect("bytes serialize to JSON"));
+    }
+
+    /// Methods and parameters of every request sent so far, oldest first
+    pub fn requests(&self) -> Vec<(String, Vec<rpc::Value>)> {
+        self.lock().requests.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Transport for MockTransport {
+    type Out = BoxFuture<'static, web3::Result<rpc::Value>>;
+
+    fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+        let mut state = self.lock();
+        state.requests.push((method.to_string(), params.clone()));
+        let id = state.requests.len();
+        (id, helpers::build_request(id, method, params))
+    }
+
+    fn send(&self, _id: RequestId, request: rpc::Call) -> Self::Out {
+        let method = match &request {
+            rpc::Call::MethodCall(call) => call.method.clone(),
+            rpc::Call::Notification(notification) => notification.method.clone(),
+            rpc::Call::Invalid { .. } => String::new(),
+        };
+
+        let mut state = self.lock();
+        let response = state
+            .queued
+            .get_mut(&method)
+            .and_then(VecDeque::pop_front)
+            .or_else(|| state.fixed.get(&method).cloned())
+            .ok_or_else(|| web3::Error::InvalidResponse(format!("no mock response for {}", method)));
+
+        future::ready(response).boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::YieldFarmingClient;
+    use web3::types::{Address, H256, U256};
+
+    const POOL_ABI: &[u8] = br#"[
+        {"type":"function","name":"balanceOf","stateMutability":"view","inputs":[{"name":"account","type":"address"}],"outputs":[{"name":"","type":"uint256"}]},
+        {"type":"function","name":"deposit","stateMutability":"nonpayable","inputs":[{"name":"amount","type":"uint256"}],"outputs":[]}
+    ]"#;
+
+    fn client(transport: &MockTransport) -> YieldFarmingClient<MockTransport> {
+        YieldFarmingClient::from_transport(transport.clone(), Address::repeat_byte(0x11), POOL_ABI).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_staked_balance_from_canned_call() {
+        let transport = MockTransport::new();
+        transport.push_call_result(&[Token::Uint(1_000.into())]);
+
+        let balance = client(&transport).get_staked_balance(Address::repeat_byte(0x22)).await.unwrap();
+        assert_eq!(balance, U256::from(1_000));
+        assert_eq!(transport.requests()[0].0, "eth_call");
+    }
+
+    #[tokio::test]
+    async fn test_deposit_returns_canned_hash() {
+        let transport = MockTransport::new();
+        let tx_hash = H256::repeat_byte(0xab);
+        transport.set_response("eth_estimateGas", serde_json::json!("0x5208"));
+        transport.set_response("eth_sendTransaction", serde_json::to_value(tx_hash).unwrap());
+
+        let result = client(&transport).deposit(100.into(), Address::repeat_byte(0x22)).await.unwrap();
+        assert_eq!(result, tx_hash);
+
+        // 21000 estimated gas with the default 20% headroom
+        let send = transport.requests().into_iter().find(|(method, _)| method == "eth_sendTransaction").unwrap();
+        assert_eq!(send.1[0]["gas"], "0x6270");
+    }
+
+    #[tokio::test]
+    async fn test_missing_response_is_an_error() {
+        let transport = MockTransport::new();
+        assert!(client(&transport).get_staked_balance(Address::zero()).await.is_err());
+    }
+}
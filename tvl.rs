@@ -0,0 +1,126 @@
+use futures::{stream, StreamExt, TryStreamExt};
+use web3::{
+    contract::Options,
+    types::{BlockId, BlockNumber, U256},
+    Transport,
+};
+
+use crate::cache::{ViewCache, STAKING_TOKEN_TVL};
+use crate::{erc20, error, retry, YieldFarmingClient, YieldFarmingError};
+
+/// Blocks queried at once by `tvl_history`
+const TVL_HISTORY_CONCURRENCY: usize = 8;
+
+/// How `total_value_locked` measured the pool's TVL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TvlSource {
+    /// The pool's own `totalValueLocked()` view
+    View,
+    /// The staking token's `balanceOf(pool)`, for pools without the view
+    StakingTokenBalance,
+}
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Get total value locked in the pool along with how it was measured.
+    ///
+    /// Uses `totalValueLocked()` when the ABI has it, otherwise the pool's
+    /// balance of its staking token. The fallback also counts tokens sent to
+    /// the pool without staking, and rewards if they are paid in the staking token.
+    pub async fn total_value_locked(&self) -> Result<(U256, TvlSource), YieldFarmingError> {
+        if self.contract.abi().function("totalValueLocked").is_ok() {
+            let tvl = self.query_with_retry("totalValueLocked", ()).await?;
+            return Ok((tvl, TvlSource::View));
+        }
+
+        let pool = self.contract.address();
+        let tvl = self.view_cache
+            .get_or_fetch(ViewCache::key(pool, STAKING_TOKEN_TVL, ()), || async {
+                let token = erc20::erc20_contract(self.web3.eth(), self.staking_token().await?)?;
+                self.retry_policy
+                    .retry(|| retry::with_timeout(self.request_timeout, async {
+                        let balance: U256 = token.query("balanceOf", (pool,), None, Options::default(), None).await?;
+                        Ok(balance)
+                    }))
+                    .await
+            })
+            .await?;
+        Ok((tvl, TvlSource::StakingTokenBalance))
+    }
+
+    /// Sample the pool's TVL at each of `blocks`, returning `(block, tvl)` pairs sorted by block.
+    ///
+    /// Measures TVL the same way as `total_value_locked`, querying up to eight
+    /// blocks at a time. Pair with `block_timestamp` for a time-indexed series.
+    /// Blocks older than the node's retained state need an archive node.
+    pub async fn tvl_history(&self, blocks: &[u64]) -> Result<Vec<(u64, U256)>, YieldFarmingError> {
+        let mut samples: Vec<(u64, U256)> = stream::iter(blocks.iter().copied())
+            .map(|block| async move { Ok::<_, YieldFarmingError>((block, self.tvl_at(BlockNumber::Number(block.into())).await?)) })
+            .buffer_unordered(TVL_HISTORY_CONCURRENCY)
+            .try_collect()
+            .await?;
+
+        samples.sort_by_key(|(block, _)| *block);
+        Ok(samples)
+    }
+
+    /// TVL as of a past block, by `totalValueLocked` or the staking token balance
+    pub(crate) async fn tvl_at(&self, block: BlockNumber) -> Result<U256, YieldFarmingError> {
+        if self.contract.abi().function("totalValueLocked").is_ok() {
+            return self.query_at_with_retry("totalValueLocked", (), Some(block)).await;
+        }
+
+        let pool = self.contract.address();
+        let token = erc20::erc20_contract(self.web3.eth(), self.staking_token().await?)?;
+        self.retry_policy
+            .retry(|| retry::with_timeout(self.request_timeout, async {
+                token
+                    .query("balanceOf", (pool,), None, Options::default(), Some(BlockId::Number(block)))
+                    .await
+                    .map_err(|err| match err {
+                        err if error::is_missing_state(&err) => YieldFarmingError::StateUnavailable(block),
+                        err => YieldFarmingError::from(err),
+                    })
+            }))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTransport;
+    use web3::{ethabi::Token, types::Address};
+
+    #[tokio::test]
+    async fn test_tvl_falls_back_to_staking_token_balance() {
+        let transport = MockTransport::new();
+        transport.push_call_result(&[Token::Address(Address::repeat_byte(0x22))]);
+        transport.push_call_result(&[Token::Uint(1_000.into())]);
+        let abi = br#"[
+            {"type":"function","name":"stakingToken","stateMutability":"view","inputs":[],"outputs":[{"name":"","type":"address"}]}
+        ]"#;
+
+        let client = YieldFarmingClient::from_transport(transport.clone(), Address::repeat_byte(0x11), abi).unwrap();
+        assert_eq!(client.total_value_locked().await.unwrap(), (1_000.into(), TvlSource::StakingTokenBalance));
+
+        let calls: Vec<_> = transport.requests().into_iter().filter(|(method, _)| method == "eth_call").collect();
+        assert_eq!(calls[1].1[0]["to"], serde_json::to_value(Address::repeat_byte(0x22)).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_tvl_history_sorted_by_block() {
+        let transport = MockTransport::new();
+        transport.set_response("eth_call", serde_json::to_value(web3::types::Bytes(web3::ethabi::encode(&[Token::Uint(9.into())]))).unwrap());
+        let abi = br#"[
+            {"type":"function","name":"totalValueLocked","stateMutability":"view","inputs":[],"outputs":[{"name":"","type":"uint256"}]}
+        ]"#;
+
+        let client = YieldFarmingClient::from_transport(transport.clone(), Address::zero(), abi).unwrap();
+        let history = client.tvl_history(&[300, 100, 200]).await.unwrap();
+        assert_eq!(history, vec![(100, 9.into()), (200, 9.into()), (300, 9.into())]);
+
+        let blocks: Vec<_> = transport.requests().into_iter().map(|(_, params)| params[1].clone()).collect();
+        assert_eq!(blocks.len(), 3);
+        assert!(blocks.contains(&serde_json::json!("0x64")));
+    }
+}
@@ -0,0 +1,115 @@
+//! Conversions between human-readable token amounts and base units (wei).
+//!
+//! Conversions go through the decimal string form of the value rather than
+//! float multiplication, so `to_wei(1.1, 18)` is exactly `1100000000000000000`.
+//! Digits beyond `decimals` are truncated toward zero, so fractional wei are
+//! always rounded down.
+
+use web3::types::U256;
+
+/// Decimals used by ether and most ERC20 tokens
+pub const ETHER_DECIMALS: u8 = 18;
+
+/// Convert a human-readable amount into base units.
+///
+/// Negative, NaN and infinite amounts convert to zero; amounts too large for
+/// a `U256` saturate at `U256::MAX`.
+pub fn to_wei(amount: f64, decimals: u8) -> U256 {
+    if !amount.is_finite() || amount <= 0.0 {
+        return U256::zero();
+    }
+
+    // `Display` for f64 prints the shortest round-tripping decimal, never exponent notation
+    parse_decimal_str(&amount.to_string(), decimals).unwrap_or(U256::MAX)
+}
+
+/// Convert an amount in base units into a human-readable value
+pub fn from_wei(amount: U256, decimals: u8) -> f64 {
+    format_decimal(amount, decimals).parse().unwrap_or(f64::MAX)
+}
+
+/// Convert ether into wei
+pub fn ether_to_wei(ether: f64) -> U256 {
+    to_wei(ether, ETHER_DECIMALS)
+}
+
+/// Convert wei into ether
+pub fn wei_to_ether(wei: U256) -> f64 {
+    from_wei(wei, ETHER_DECIMALS)
+}
+
+/// Render base units as an exact decimal string without trailing zeros, e.g. `"1.5"`
+pub(crate) fn format_decimal(amount: U256, decimals: u8) -> String {
+    let digits = amount.to_string();
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return digits;
+    }
+
+    let padded = format!("{:0>width$}", digits, width = decimals + 1);
+    let (int_part, frac_part) = padded.split_at(padded.len() - decimals);
+    let frac_part = frac_part.trim_end_matches('0');
+    if frac_part.is_empty() {
+        int_part.to_string()
+    } else {
+        format!("{}.{}", int_part, frac_part)
+    }
+}
+
+/// Parse a non-negative decimal string such as `"12.5"` into base units.
+///
+/// Returns `None` for malformed input or when the result overflows a `U256`.
+pub(crate) fn parse_decimal_str(value: &str, decimals: u8) -> Option<U256> {
+    let value = value.trim();
+    let (int_part, frac_part) = value.split_once('.').unwrap_or((value, ""));
+    if int_part.is_empty() && frac_part.is_empty() {
+        return None;
+    }
+    if !int_part.chars().chain(frac_part.chars()).all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let decimals = decimals as usize;
+    let frac: String = frac_part.chars().chain(std::iter::repeat('0')).take(decimals).collect();
+    let digits = format!("{}{}", int_part, frac);
+    let digits = digits.trim_start_matches('0');
+    if digits.is_empty() {
+        return Some(U256::zero());
+    }
+
+    U256::from_dec_str(digits).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_wei_is_exact() {
+        assert_eq!(ether_to_wei(1.0), U256::from(1_000_000_000_000_000_000u64));
+        assert_eq!(ether_to_wei(1.1), U256::from(1_100_000_000_000_000_000u64));
+        assert_eq!(to_wei(2.5, 6), U256::from(2_500_000));
+        assert_eq!(to_wei(-1.0, 18), U256::zero());
+    }
+
+    #[test]
+    fn test_to_wei_truncates_fractional_wei() {
+        assert_eq!(to_wei(1.999, 2), U256::from(199));
+        assert_eq!(to_wei(0.0000001, 6), U256::zero());
+    }
+
+    #[test]
+    fn test_from_wei() {
+        assert_eq!(wei_to_ether(U256::from(1_500_000_000_000_000_000u64)), 1.5);
+        assert_eq!(from_wei(U256::from(42), 6), 0.000042);
+        assert_eq!(from_wei(U256::from(7), 0), 7.0);
+    }
+
+    #[test]
+    fn test_parse_decimal_str() {
+        assert_eq!(parse_decimal_str("12.5", 2), Some(U256::from(1250)));
+        assert_eq!(parse_decimal_str(".5", 1), Some(U256::from(5)));
+        assert_eq!(parse_decimal_str("1e5", 2), None);
+        assert_eq!(parse_decimal_str("", 2), None);
+    }
+}
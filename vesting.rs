@@ -0,0 +1,77 @@
+use web3::{
+    types::{Address, U256},
+    Transport,
+};
+
+use crate::{YieldFarmingClient, YieldFarmingError};
+
+/// How much of a user's rewards is vesting and how much can be claimed now.
+///
+/// Each field is `None` when the pool's ABI has no matching view.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LockInfo {
+    /// `lockedBalance(user)`: rewards still vesting
+    pub locked: Option<U256>,
+    /// `unlockTime(user)`: Unix timestamp at which the locked rewards vest
+    pub unlock_time: Option<u64>,
+    /// `claimableNow(user)`: rewards `claim_rewards` would release immediately
+    pub claimable_now: Option<U256>,
+}
+
+impl LockInfo {
+    /// Whether the pool reports any vesting views at all
+    pub fn has_vesting(&self) -> bool {
+        self.locked.is_some() || self.unlock_time.is_some() || self.claimable_now.is_some()
+    }
+}
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Read the vesting status of `account`'s rewards for pools that lock rewards instead of paying them instantly.
+    ///
+    /// On such pools `claim_rewards` only releases `claimable_now`. Views the ABI lacks are skipped.
+    pub async fn reward_lock_info(&self, account: Address) -> Result<LockInfo, YieldFarmingError> {
+        let (locked, unlock_time, claimable_now) = futures::try_join!(
+            self.optional_view::<U256, _>("lockedBalance", (account,)),
+            self.optional_view::<U256, _>("unlockTime", (account,)),
+            self.optional_view::<U256, _>("claimableNow", (account,)),
+        )?;
+
+        Ok(LockInfo {
+            locked,
+            unlock_time: unlock_time.map(|time| time.low_u64()),
+            claimable_now,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTransport;
+    use web3::ethabi::Token;
+
+    const VESTING_ABI: &[u8] = br#"[
+        {"type":"function","name":"lockedBalance","stateMutability":"view","inputs":[{"name":"user","type":"address"}],"outputs":[{"name":"","type":"uint256"}]},
+        {"type":"function","name":"claimableNow","stateMutability":"view","inputs":[{"name":"user","type":"address"}],"outputs":[{"name":"","type":"uint256"}]}
+    ]"#;
+
+    #[tokio::test]
+    async fn test_reward_lock_info() {
+        let transport = MockTransport::new();
+        transport.push_call_result(&[Token::Uint(750.into())]);
+        transport.push_call_result(&[Token::Uint(250.into())]);
+
+        let client = YieldFarmingClient::from_transport(transport, Address::zero(), VESTING_ABI).unwrap();
+        let info = client.reward_lock_info(Address::repeat_byte(0x01)).await.unwrap();
+        assert_eq!(
+            info,
+            LockInfo {
+                locked: Some(750.into()),
+                unlock_time: None,
+                claimable_now: Some(250.into()),
+            }
+        );
+        assert!(info.has_vesting());
+        assert!(!LockInfo::default().has_vesting());
+    }
+}
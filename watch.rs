@@ -0,0 +1,85 @@
+use futures::{stream, Stream};
+use std::time::Duration;
+use web3::{
+    types::{Address, U256},
+    Transport,
+};
+
+use crate::{UserPosition, YieldFarmingClient, YieldFarmingError};
+
+/// A change in a watched position
+#[derive(Debug, Clone, PartialEq)]
+pub struct PositionDelta {
+    /// Position last emitted, or `None` for the first one
+    pub previous: Option<UserPosition>,
+    pub current: UserPosition,
+}
+
+impl<T: Transport> YieldFarmingClient<T> {
+    /// Poll `account`'s position every `poll_interval`, emitting it whenever the staked balance or
+    /// pending rewards change.
+    ///
+    /// Works over any transport, including HTTP endpoints that can't subscribe to events.
+    pub fn watch_position(&self, account: Address, poll_interval: Duration) -> impl Stream<Item = Result<PositionDelta, YieldFarmingError>> + '_ {
+        self.watch_position_with_threshold(account, poll_interval, U256::zero())
+    }
+
+    /// Like `watch_position`, but ignore changes of at most `threshold` base units.
+    ///
+    /// The first poll is always emitted. Changes are measured against the last
+    /// emitted position, so slow drift still surfaces once it adds up past the
+    /// threshold. Failed polls are emitted as errors and polling continues.
+    pub fn watch_position_with_threshold(
+        &self,
+        account: Address,
+        poll_interval: Duration,
+        threshold: U256,
+    ) -> impl Stream<Item = Result<PositionDelta, YieldFarmingError>> + '_ {
+        stream::unfold((None::<UserPosition>, true), move |(previous, first)| async move {
+            if !first {
+                tokio::time::sleep(poll_interval).await;
+            }
+            loop {
+                match self.get_user_position(account).await {
+                    Err(err) => return Some((Err(err), (previous, false))),
+                    Ok(current) => {
+                        if previous.as_ref().is_none_or(|previous| exceeds_threshold(previous, &current, threshold)) {
+                            let delta = PositionDelta { previous, current: current.clone() };
+                            return Some((Ok(delta), (Some(current), false)));
+                        }
+                    }
+                }
+                tokio::time::sleep(poll_interval).await;
+            }
+        })
+    }
+}
+
+/// Whether the staked balance or pending rewards moved by more than `threshold`
+fn exceeds_threshold(previous: &UserPosition, current: &UserPosition, threshold: U256) -> bool {
+    let moved = |a: U256, b: U256| if a > b { a - b } else { b - a };
+    moved(previous.staked_balance, current.staked_balance) > threshold
+        || moved(previous.pending_rewards, current.pending_rewards) > threshold
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(staked_balance: u64, pending_rewards: u64) -> UserPosition {
+        UserPosition {
+            staked_balance: staked_balance.into(),
+            pending_rewards: pending_rewards.into(),
+            total_value_locked: U256::zero(),
+            current_apy: None,
+        }
+    }
+
+    #[test]
+    fn test_exceeds_threshold() {
+        assert!(!exceeds_threshold(&position(100, 10), &position(100, 10), U256::zero()));
+        assert!(exceeds_threshold(&position(100, 10), &position(100, 11), U256::zero()));
+        assert!(!exceeds_threshold(&position(100, 10), &position(100, 15), 5.into()));
+        assert!(exceeds_threshold(&position(100, 10), &position(94, 10), 5.into()));
+    }
+}
@@ -0,0 +1,161 @@
+//! `yf`: quick command-line access to a yield farming pool.
+//!
+//! Every subcommand is a thin wrapper over `YieldFarmingClient`; this file only
+//! parses arguments and formats output.
+
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use web3::types::{Address, H256};
+
+use blockchain_yield_farming::{parse_checked_address, HttpYieldFarmingClient, Signer, TokenAmount, YieldFarmingClient, YieldFarmingError};
+
+#[derive(Debug, Parser)]
+#[command(name = "yf", about = "Inspect and interact with a yield farming pool")]
+struct Cli {
+    /// HTTP RPC endpoint
+    #[arg(long, env = "YF_RPC_URL")]
+    rpc_url: String,
+
+    /// Pool contract address
+    #[arg(long, env = "YF_CONTRACT")]
+    contract: String,
+
+    /// Path to the pool's JSON ABI
+    #[arg(long, env = "YF_ABI")]
+    abi: PathBuf,
+
+    /// Hex private key used to sign deposits and withdrawals
+    #[arg(long, env = "YF_PRIVATE_KEY", hide_env_values = true)]
+    private_key: Option<String>,
+
+    /// Block explorer base URL for transaction links, defaulting to the chain's main explorer
+    #[arg(long, env = "YF_EXPLORER")]
+    explorer: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Show an account's staked balance
+    Balance {
+        /// Account to query, defaulting to the private key's address
+        #[arg(long)]
+        account: Option<String>,
+    },
+    /// Show an account's pending rewards
+    Pending {
+        /// Account to query, defaulting to the private key's address
+        #[arg(long)]
+        account: Option<String>,
+    },
+    /// Show the pool's total value locked
+    Tvl,
+    /// Show the pool's current APY as reported by the contract, if it reports one
+    Apy,
+    /// Deposit staking tokens, e.g. `yf deposit 1.5`
+    Deposit { amount: String },
+    /// Withdraw staked tokens, e.g. `yf withdraw 1.5`
+    Withdraw { amount: String },
+}
+
+#[tokio::main]
+async fn main() {
+    if let Err(err) = run(Cli::parse()).await {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), YieldFarmingError> {
+    let contract = parse_checked_address(&cli.contract)?;
+    let client = YieldFarmingClient::from_abi_file(&cli.rpc_url, contract, &cli.abi)?;
+
+    match cli.command {
+        Command::Balance { account } => {
+            let account = resolve_account(account.as_deref(), cli.private_key.as_deref())?;
+            let (balance, symbol) = futures::try_join!(client.get_staked_balance_typed(account), staking_symbol(&client))?;
+            println!("{} {}", balance, symbol);
+        }
+        Command::Pending { account } => {
+            let account = resolve_account(account.as_deref(), cli.private_key.as_deref())?;
+            let pending = client.get_pending_rewards_typed(account).await?;
+            let symbol = client.token_symbol(client.reward_token().await?).await?;
+            println!("{} {}", pending, symbol);
+        }
+        Command::Tvl => {
+            let (tvl, decimals, symbol) = futures::try_join!(
+                client.get_total_value_locked(),
+                client.token_decimals(),
+                staking_symbol(&client),
+            )?;
+            println!("{} {}", TokenAmount::new(tvl, decimals), symbol);
+        }
+        Command::Apy => {
+            match client.get_current_apy().await? {
+                Some(apy) => println!("{}%", apy),
+                None => println!("pool does not report an APY"),
+            }
+        }
+        Command::Deposit { amount } => {
+            let signer = signer(&client, cli.private_key.as_deref()).await?;
+            let amount = parse_amount(&amount, client.token_decimals().await?)?;
+            let tx_hash = client.deposit_signed(amount.into(), signer.address(), &signer).await?;
+            print_tx(tx_hash, signer.chain_id(), cli.explorer.as_deref());
+        }
+        Command::Withdraw { amount } => {
+            let signer = signer(&client, cli.private_key.as_deref()).await?;
+            let amount = parse_amount(&amount, client.token_decimals().await?)?;
+            let tx_hash = client.withdraw_signed(amount.into(), signer.address(), &signer).await?;
+            print_tx(tx_hash, signer.chain_id(), cli.explorer.as_deref());
+        }
+    }
+
+    Ok(())
+}
+
+async fn staking_symbol(client: &HttpYieldFarmingClient) -> Result<String, YieldFarmingError> {
+    client.token_symbol(client.staking_token().await?).await
+}
+
+/// Use `--account` if given, otherwise the private key's address
+fn resolve_account(account: Option<&str>, private_key: Option<&str>) -> Result<Address, YieldFarmingError> {
+    match (account, private_key) {
+        (Some(account), _) => parse_checked_address(account),
+        // The chain id only matters for signing, which reads never do
+        (None, Some(key)) => Ok(Signer::from_hex(key, 0)?.address()),
+        (None, None) => Err(YieldFarmingError::MissingField("--account or YF_PRIVATE_KEY")),
+    }
+}
+
+async fn signer(client: &HttpYieldFarmingClient, private_key: Option<&str>) -> Result<Signer, YieldFarmingError> {
+    let key = private_key.ok_or(YieldFarmingError::MissingField("--private-key or YF_PRIVATE_KEY"))?;
+    let chain_id = client.verify_chain().await?;
+    Signer::from_hex(key, chain_id)
+}
+
+fn parse_amount(amount: &str, decimals: u8) -> Result<TokenAmount, YieldFarmingError> {
+    TokenAmount::from_decimal_str(amount, decimals).ok_or_else(|| YieldFarmingError::InvalidAmount(amount.to_string()))
+}
+
+fn print_tx(tx_hash: H256, chain_id: u64, explorer: Option<&str>) {
+    match explorer.or_else(|| default_explorer(chain_id)) {
+        Some(explorer) => println!("{}/tx/{:?}", explorer.trim_end_matches('/'), tx_hash),
+        None => println!("{:?}", tx_hash),
+    }
+}
+
+/// Main block explorer of well-known chains
+fn default_explorer(chain_id: u64) -> Option<&'static str> {
+    match chain_id {
+        1 => Some("https://etherscan.io"),
+        10 => Some("https://optimistic.etherscan.io"),
+        56 => Some("https://bscscan.com"),
+        137 => Some("https://polygonscan.com"),
+        8453 => Some("https://basescan.org"),
+        42161 => Some("https://arbiscan.io"),
+        11155111 => Some("https://sepolia.etherscan.io"),
+        _ => None,
+    }
+}